@@ -1,9 +1,13 @@
 use std::cmp::Ordering;
 use std::fmt::{Debug, Display, Formatter, Write};
+use std::ops::Range;
 use std::{fmt, mem};
 
-use crate::bfasm::binterp::{BFError, BFInterpreter, BFOp};
-mod binterp;
+use crate::bfasm::binterp::{BFError, BFInterpreter, BFOp, CellModel, EofPolicy, Profile, Tracer};
+pub(crate) mod binterp;
+// bad ops are found by their overflow panics
+#[cfg(all(test, debug_assertions))]
+mod fuzz;
 
 use Type::EmptyCell as EC;
 // use Type as T;
@@ -13,13 +17,13 @@ use crate::bfasm::BfasmError::TypeMismatch;
 
 macro_rules! label {
     ($dst:expr, $($arg:tt)*) => {
-        write!($dst, $($arg)*).unwrap();
-
         // if let BfasmWriter::BFInterp(binterp, _) = &mut $dst {
         //     binterp.instructions.push(BFOp::Lable)
         // }
 
-        $dst.as_mut_bfops().push(BFOp::Lable)
+        if $dst.is_enabled() {
+            $dst.as_mut_bfops().push(BFOp::Lable(format!($($arg)*).trim_end().to_string(), None))
+        }
     };
 }
 
@@ -55,6 +59,24 @@ impl Type {
     //     }
     // }
 
+    // the largest value any cell of the slice holds
+    fn max_cell(slice: &[Type]) -> u32 {
+        slice.iter().map(|x| {
+            match x {
+                Type::U32(val) => *val,
+                Type::I32(val) => val.unsigned_abs(),
+                Type::Bool(_) | Type::EmptyCell => 1,
+                Type::Char(val) => *val as u32,
+                Type::FString(val) | Type::IString(val) => {
+                    val.iter().map(|x| *x as u32).max().unwrap_or(0).max(val.len() as u32)
+                }
+                Type::Array(val) => {
+                    val.iter().map(|x| x + 1).max().unwrap_or(0).max(val.len() as u32)
+                }
+            }
+        }).max().unwrap_or(0)
+    }
+
     fn len_slice(slice: &[Type]) -> usize {
         slice.iter().map(|x| {
             match x {
@@ -224,14 +246,23 @@ pub enum BfasmError {
     // type errors
     TypeMismatch(Vec<EmptyType>, Vec<Type>),
     InvalidMatchArm(usize),
+    CellOverflow(u32),
 
     // value errors
     OpError(OpError),
+
+    // the code didn't do what the op expected
+    Diverged(Box<Divergence>),
+
+    // the loops ran for more iterations than the step limit
+    StepLimit,
 }
 
 #[derive(Debug, Clone)]
 pub enum OpError {
     InvalidStringIndex(usize),
+    InvalidArrayIndex(usize),
+    CharNotFound(u8),
     ErrorsInMatch(Vec<OpError>),
     Underflow,
 }
@@ -248,21 +279,54 @@ impl Display for BfasmError {
             }
             // BfasmError::InvalidIndex(index) => write!(f, "Invalid array index of {index}"),
             BfasmError::OpError(OpError::InvalidStringIndex(index)) => write!(f, "Invalid string index of {index}"),
+            BfasmError::OpError(OpError::InvalidArrayIndex(index)) => write!(f, "Invalid array index of {index}"),
+            BfasmError::OpError(OpError::CharNotFound(char)) => write!(f, "Char {} not found", *char as char),
             BfasmError::InvalidMatchArm(index) => {
                 write!(f, "Invalid match arm {index} with mismatching array types")
             }
+            BfasmError::CellOverflow(val) => write!(f, "Value {val} doesn't fit in a cell"),
             BfasmError::OpError(OpError::Underflow) => {
                 write!(f, "Underflow")
             }
             BfasmError::OpError(OpError::ErrorsInMatch(err)) => {
                 write!(f, "Error(s) inside block with the first as {:?}", err[0])
             }
+            BfasmError::Diverged(divergence) => write!(f, "{divergence}"),
+            BfasmError::StepLimit => write!(f, "The loops ran for more than the step limit"),
         }
     }
 }
 
 impl std::error::Error for BfasmError {}
 
+// the first op whose code left the interp different to the array
+#[derive(Debug, Clone)]
+pub struct Divergence {
+    pub op: BfasmOps,
+    pub reason: String,
+
+    // the typed tape and the index of the type the pointer should be at
+    pub expected: Vec<Type>,
+    pub index: usize,
+    pub expected_pointer: usize,
+
+    pub actual: Vec<u32>,
+    pub actual_pointer: usize,
+
+    // the code written by the op starting at code_start
+    pub code: Vec<BFOp>,
+    pub code_start: usize,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} diverged: {}", self.op.name(), self.reason)?;
+        writeln!(f, "  expected {:?} with the pointer at {}", self.expected, self.expected_pointer)?;
+        writeln!(f, "  found {:?} with the pointer at {}", self.actual, self.actual_pointer)?;
+        write!(f, "  after {} at instruction {}", BFOp::as_code(&self.code), self.code_start)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum BfasmOps {
     Set(usize, Type),
@@ -272,6 +336,9 @@ pub enum BfasmOps {
     CopyVal(usize),
     I32Add(usize),
     Input(usize, Type),
+    InputLine(usize, String),
+    InputToEnd(usize, String),
+    InputExact(usize, String),
     StrIndex(usize),
     Print(usize),
     StrPushF(usize),
@@ -281,6 +348,7 @@ pub enum BfasmOps {
     ArrayIndexF(usize),
     ArrayIndex(usize),
     ArraySet(usize),
+    ArrayFlipIndex(usize),
     Len(usize),
     U32Add(usize),
     U32SubUnchecked(usize),
@@ -292,10 +360,29 @@ pub enum BfasmOps {
     LessThan(usize),
     Equals(usize),
     CharToU32(usize),
+    StrEquals(usize, usize),
+    StrStartsWith(usize, usize),
+    StrContains(usize),
+    StrFind(usize),
+    // the ops of a statement and the line and column it starts at
+    Source(usize, usize, Vec<BfasmOps>),
 }
 
 impl BfasmOps {
     pub fn exec_instruct(&self, bfasm: &mut Bfasm) -> Result<(), BfasmError> {
+        // the statement's ops are run and checked as if they weren't wrapped
+        if let BfasmOps::Source(line, column, code) = self {
+            bfasm.source(*line, *column);
+
+            return match BfasmOps::full_exec(code, bfasm)? {
+                None => Ok(()),
+                Some(mut errs) if errs.len() == 1 => Err(BfasmError::OpError(errs.remove(0))),
+                Some(errs) => Err(BfasmError::OpError(OpError::ErrorsInMatch(errs))),
+            };
+        }
+
+        let start = bfasm.output.as_bfops().len();
+
         let res = match self {
             BfasmOps::Set(index, bftype) => bfasm.set(*index, bftype.clone()),
             BfasmOps::MoveTo(index) => {
@@ -303,13 +390,13 @@ impl BfasmOps {
                 Ok(())
             }
             BfasmOps::MoveType(index, targetindex) => bfasm.move_type(*index, *targetindex),
-            BfasmOps::Clear(index) => {
-                bfasm.clear(*index);
-                Ok(())
-            }
+            BfasmOps::Clear(index) => bfasm.clear(*index),
             BfasmOps::CopyVal(index) => bfasm.copy_val(*index),
             BfasmOps::I32Add(index) => bfasm.add_i32(*index),
             BfasmOps::Input(index, bftype) => bfasm.input(*index, bftype.clone()),
+            BfasmOps::InputLine(index, str) => bfasm.input_line(*index, str),
+            BfasmOps::InputToEnd(index, str) => bfasm.input_to_end(*index, str),
+            BfasmOps::InputExact(index, str) => bfasm.input_exact(*index, str),
             BfasmOps::StrIndex(index) => bfasm.index_str(*index),
             BfasmOps::Print(index) => bfasm.print(*index),
             BfasmOps::StrPushF(index) => bfasm.str_push_front(*index),
@@ -319,6 +406,7 @@ impl BfasmOps {
             BfasmOps::ArrayIndexF(index) => bfasm.array_index(*index),
             BfasmOps::ArrayIndex(index) => bfasm.array_index_back(*index),
             BfasmOps::ArraySet(index) => bfasm.array_set_back(*index),
+            BfasmOps::ArrayFlipIndex(index) => bfasm.flip_array_index(*index),
             BfasmOps::Len(index) => bfasm.get_len(*index),
             BfasmOps::U32Add(index) => bfasm.add_u32(*index),
             BfasmOps::U32SubUnchecked(index) => bfasm.unsafe_sub_u32(*index),
@@ -330,27 +418,115 @@ impl BfasmOps {
             BfasmOps::LessThan(index) => bfasm.less_than(*index),
             BfasmOps::Equals(index) => bfasm.equals(*index),
             BfasmOps::CharToU32(index) => bfasm.char_to_u32(*index),
+            BfasmOps::StrEquals(index, other) => bfasm.str_equals(*index, *other),
+            BfasmOps::StrStartsWith(index, other) => bfasm.str_starts_with(*index, *other),
+            BfasmOps::StrContains(index) => bfasm.str_contains(*index),
+            BfasmOps::StrFind(index) => bfasm.str_find(*index),
+            BfasmOps::Source(..) => unreachable!(),
         };
 
-        if let BfasmWriter::BFInterp(binterp, _) = &mut bfasm.output {
-            binterp.input = bfasm.expected_input.clone();
+        // the labels written by this op
+        bfasm.output.as_mut_bfops()[start..].iter_mut().for_each(|op| {
+            if let BFOp::Lable(_, name @ None) = op {
+                *name = Some(self.name())
+            }
+        });
+
+        Tracer::trace(&bfasm.trace, || format!("{:?}\n  {:?} at {}", self, bfasm.array, bfasm.index));
+
+        let max = Type::max_cell(&bfasm.array);
 
-            dbg!(self, &bfasm.array, bfasm.index);
+        if max > bfasm.cells.max() {
+            return Err(BfasmError::CellOverflow(max));
+        }
+
+        // the code of an op that errs is meant to fail so it isn't checked
+        // and a loop that ran out of steps hasn't written all of its code
+        if !bfasm.verify || matches!(res, Err(BfasmError::OpError(_) | BfasmError::StepLimit)) {
+            return res;
+        }
+
+        if let BfasmWriter::BFInterp(binterp, true) = &mut bfasm.output {
+            binterp.input = bfasm.expected_input.clone();
+            binterp.eof = bfasm.eof;
+            binterp.cells = bfasm.cells;
+            binterp.check_cells = true;
+            binterp.step_limit = bfasm.step_limit;
 
-            binterp.label_run().unwrap();
+            let run = binterp.label_run();
 
             let interp = mem::take(binterp);
 
-            assert!(bfasm.cmp_to_interp(&interp));
+            let reason = match run {
+                Ok(()) => bfasm.divergence(&interp),
+                Err(err) => Some(err.to_string()),
+            };
 
             let BfasmWriter::BFInterp(binterp, _) = &mut bfasm.output else {unreachable!()};
 
             let _ = mem::replace(binterp, interp);
+
+            if let Some(reason) = reason {
+                let BfasmWriter::BFInterp(binterp, _) = &bfasm.output else {unreachable!()};
+
+                return Err(BfasmError::Diverged(Box::new(Divergence {
+                    op: self.clone(),
+                    reason,
+                    expected: bfasm.array.clone(),
+                    index: bfasm.index,
+                    expected_pointer: Type::len_slice(&bfasm.array[0..bfasm.index]),
+                    actual: binterp.array.clone(),
+                    actual_pointer: binterp.array_index,
+                    code: binterp.instructions[start..].to_vec(),
+                    code_start: start,
+                })));
+            }
         }
 
         res
     }
 
+    pub fn name(&self) -> &'static str {
+        match self {
+            BfasmOps::Set(..) => "Set",
+            BfasmOps::MoveTo(..) => "MoveTo",
+            BfasmOps::MoveType(..) => "MoveType",
+            BfasmOps::Clear(..) => "Clear",
+            BfasmOps::CopyVal(..) => "CopyVal",
+            BfasmOps::I32Add(..) => "I32Add",
+            BfasmOps::Input(..) => "Input",
+            BfasmOps::InputLine(..) => "InputLine",
+            BfasmOps::InputToEnd(..) => "InputToEnd",
+            BfasmOps::InputExact(..) => "InputExact",
+            BfasmOps::StrIndex(..) => "StrIndex",
+            BfasmOps::Print(..) => "Print",
+            BfasmOps::StrPushF(..) => "StrPushF",
+            BfasmOps::StrPush(..) => "StrPush",
+            BfasmOps::ArrayPush(..) => "ArrayPush",
+            BfasmOps::ArrayPushF(..) => "ArrayPushF",
+            BfasmOps::ArrayIndexF(..) => "ArrayIndexF",
+            BfasmOps::ArrayIndex(..) => "ArrayIndex",
+            BfasmOps::ArraySet(..) => "ArraySet",
+            BfasmOps::ArrayFlipIndex(..) => "ArrayFlipIndex",
+            BfasmOps::Len(..) => "Len",
+            BfasmOps::U32Add(..) => "U32Add",
+            BfasmOps::U32SubUnchecked(..) => "U32SubUnchecked",
+            BfasmOps::InsertEC(..) => "InsertEC",
+            BfasmOps::CharMatch(..) => "CharMatch",
+            BfasmOps::BoolIf(..) => "BoolIf",
+            BfasmOps::BoolWhile(..) => "BoolWhile",
+            BfasmOps::GreaterThan(..) => "GreaterThan",
+            BfasmOps::LessThan(..) => "LessThan",
+            BfasmOps::Equals(..) => "Equals",
+            BfasmOps::CharToU32(..) => "CharToU32",
+            BfasmOps::StrEquals(..) => "StrEquals",
+            BfasmOps::StrStartsWith(..) => "StrStartsWith",
+            BfasmOps::StrContains(..) => "StrContains",
+            BfasmOps::StrFind(..) => "StrFind",
+            BfasmOps::Source(..) => "Source",
+        }
+    }
+
     pub fn exec(code: &[BfasmOps], bfasm: &mut Bfasm) -> Result<(), (usize, BfasmError)> {
 
         for (index, oper) in code.iter().enumerate() {
@@ -360,6 +536,19 @@ impl BfasmOps {
         Ok(())
     }
 
+    // runs the ops and returns the range of code each one wrote, it stops at the first op that errs
+    pub fn exec_ranges(code: &[BfasmOps], bfasm: &mut Bfasm) -> Result<Vec<Range<usize>>, BfasmError> {
+        code.iter()
+            .map(|oper| {
+                let start = bfasm.output.as_bfops().len();
+
+                oper.exec_instruct(bfasm)?;
+
+                Ok(start..bfasm.output.as_bfops().len())
+            })
+            .collect()
+    }
+
     pub fn full_exec(code: &[BfasmOps], bfasm: &mut Bfasm) -> Result<Option<Vec<OpError>>, BfasmError> {
 
         // let errs: Vec<OpError> = code.iter().filter_map(|oper| {
@@ -375,7 +564,14 @@ impl BfasmOps {
         for oper in code {
             match oper.exec_instruct(bfasm) {
                 Ok(()) => {}
-                Err(BfasmError::OpError(err)) => {errs.push(err)}
+                Err(BfasmError::OpError(err)) => {
+                    errs.push(err);
+
+                    // the checked code stops at the op that errs so nothing after it can be checked
+                    if bfasm.is_checked() {
+                        break;
+                    }
+                }
                 Err(x) => {return Err(x);},
             }
         }
@@ -388,7 +584,16 @@ impl BfasmOps {
     }
 }
 
+// how much code an op wrote and how many of those instructions were run
+#[derive(Debug, Clone)]
+pub struct OpCost {
+    pub op: BfasmOps,
+    pub instructions: usize,
+    pub steps: usize,
+}
+
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum BfasmWriter {
     BFOps(Vec<BFOp>, bool),
     BFInterp(BFInterpreter, bool),
@@ -433,7 +638,10 @@ impl BfasmWriter {
         // let bfops = self.as_bfops();
         // code.iter().for_each(|x| bfops.push(x.clone()))
 
-        self.as_mut_bfops().extend(code);
+        if self.is_enabled() {
+            // labels inside of a block would stop a label run partway through it
+            self.as_mut_bfops().extend(code.into_iter().filter(|op| !matches!(op, BFOp::Lable(..))));
+        }
 
     }
 
@@ -459,7 +667,7 @@ impl BfasmWriter {
     // }
 
     // TODO: doesnt have to be an option
-    fn as_bfops(&self) -> &Vec<BFOp> {
+    pub fn as_bfops(&self) -> &Vec<BFOp> {
         match self {
             BfasmWriter::BFOps(str, _) => {str}
             BfasmWriter::BFInterp(binterp, _) => {&binterp.instructions}
@@ -474,6 +682,19 @@ impl BfasmWriter {
         }
     }
 
+    // the interp has already run the code so it is left as finished
+    fn peephole(&mut self) -> Result<(), BFError> {
+        let code = BFOp::peephole(self.as_bfops())?;
+
+        if let BfasmWriter::BFInterp(binterp, _) = self {
+            binterp.instruction_index = code.len();
+        }
+
+        *self.as_mut_bfops() = code;
+
+        Ok(())
+    }
+
     fn is_enabled(&self) -> bool {
         match self {
             BfasmWriter::BFOps(_, b)| BfasmWriter::BFInterp(_, b) => {*b}
@@ -504,8 +725,25 @@ pub struct Bfasm {
     pub index: usize,
     pub expected_input: String,
     pub expected_output: String,
+    pub eof: EofPolicy,
+    pub cells: CellModel,
+
+    // traces each op and the tape after it
+    pub trace: Option<Tracer>,
+
+    // the most steps the code is run for when it is checked
+    pub step_limit: Option<usize>,
+    // the fewest steps the code of the loops the ops have run would take
+    // so an endless loop runs out of steps instead of running forever
+    pub loop_steps: usize,
+
+    // checks the code after each op that doesn't err
+    pub verify: bool,
 }
 
+// a miscompiled or endless loop errors here instead of running forever
+pub(crate) const STEP_LIMIT: usize = 100_000_000;
+
 // pub type BfasmCode = Vec<Box<dyn Fn(&mut Bfasm) -> Result<(), BfasmError>>>;
 
 // impl From<&Bfasm> for Vec<u32> {
@@ -532,27 +770,106 @@ impl Bfasm {
             index: 0,
             expected_input: String::new(),
             expected_output: String::new(),
+            eof: EofPolicy::default(),
+            cells: CellModel::U32,
+            trace: None,
+            step_limit: Some(STEP_LIMIT),
+            loop_steps: 0,
+            verify: true,
         }
     }
 
     pub fn test_run(&mut self) -> Result<bool, BFError> {
 
-        let mut interp = BFInterpreter::new(self.output.as_bfops().clone(), self.expected_input.chars().collect());
+        let mut interp = self.new_interp();
 
         interp.run()?;
 
         // println!("Found output: {}", output);
-        println!("Expected output: \"{}\"", self.expected_output);
+        Tracer::trace(&self.trace, || format!("Expected output: \"{}\"", self.expected_output));
 
         Ok(self.cmp_to_interp(&interp))
     }
 
+    // runs all of the code with the expected input to find the cost of each op
+    // labels aren't counted as instructions
+    pub fn costs(&self, code: &[BfasmOps], ranges: &[Range<usize>]) -> Result<Vec<OpCost>, BFError> {
+        let instructions = self.output.as_bfops();
+
+        let hits = self.new_interp().profile()?;
+
+        Ok(code
+            .iter()
+            .zip(ranges)
+            .map(|(op, range)| {
+                let (instructions, steps) = instructions[range.clone()]
+                    .iter()
+                    .zip(&hits[range.clone()])
+                    .filter(|(op, _)| !matches!(op, BFOp::Lable(..) | BFOp::Source(..) | BFOp::Comment(_)))
+                    .fold((0, 0), |(instructions, steps), (_, hits)| {
+                        (instructions + 1, steps + hits)
+                    });
+
+                OpCost {
+                    op: op.clone(),
+                    instructions,
+                    steps,
+                }
+            })
+            .collect())
+    }
+
+    // runs all of the code with the expected input and counts where the steps were spent
+    pub fn profile(&self) -> Result<Profile, BFError> {
+        self.new_interp().profile_labels()
+    }
+
+    // where each part of the code came from as json
+    pub fn source_map(&self) -> String {
+        BFOp::source_map(self.output.as_bfops())
+    }
+
+    // if the code is run and checked after each op
+    fn is_checked(&self) -> bool {
+        self.verify && matches!(self.output, BfasmWriter::BFInterp(_, true))
+    }
+
+    fn new_interp(&self) -> BFInterpreter {
+        BFInterpreter {
+            eof: self.eof,
+            cells: self.cells,
+            check_cells: true,
+            step_limit: self.step_limit,
+            ..BFInterpreter::new(self.output.as_bfops().clone(), self.expected_input.clone())
+        }
+    }
+
+    // should only be used once all of the ops have been run
+    pub fn peephole(&mut self) -> Result<(), BFError> {
+        self.output.peephole()
+    }
+
     fn cmp_to_interp(&mut self, interp: &BFInterpreter) -> bool {
+        match self.divergence(interp) {
+            Some(reason) => {
+                Tracer::trace(&self.trace, || reason);
+                false
+            }
+            None => true,
+        }
+    }
+
+    // why the interp doesn't match the array
+    fn divergence(&mut self, interp: &BFInterpreter) -> Option<String> {
+
+        // the interp would have wrapped the value
+        if Type::max_cell(&self.array) > self.cells.max() {
+            return Some(format!("Value {} doesn't fit in a cell", Type::max_cell(&self.array)));
+        }
 
         self.get(self.index);
 
-        let expected_index = dbg!(Type::len_slice(dbg!(&self.array[0..self.index])));
-        dbg!(self.index, interp.array_index);
+        let expected_index = Type::len_slice(&self.array[0..self.index]);
 
         // cmp the array, output, and pointer
 
@@ -647,21 +964,25 @@ impl Bfasm {
                     res
                 }
             } {
-                dbg!(item, index);
-                return false;
+                return Some(format!("{:?} doesn't match the cells before {}", item, index));
             }
 
         };
 
         // make sure x did miss any values
-        if index < interp.array.len() {
-            if !interp.array[index..].iter().all(|x| *x == 0){
-                dbg!();
-                return false;
-            }
+        if index < interp.array.len() && !interp.array[index..].iter().all(|x| *x == 0) {
+            return Some(format!("Extra values after {}: {:?}", index, &interp.array[index..]));
+        }
+
+        if interp.array_index != expected_index {
+            return Some(format!("Pointer at {} expected {}", interp.array_index, expected_index));
+        }
+
+        if interp.output != self.expected_output {
+            return Some(format!("Output {:?} expected {:?}", interp.output, self.expected_output));
         }
 
-        dbg!(interp.array_index == expected_index) && dbg!(interp.output == self.expected_output)
+        None
     }
 
     fn get_slice(&mut self, index: usize, length: usize) -> &mut [Type] {
@@ -697,17 +1018,6 @@ impl Bfasm {
         self.array.get_mut(index).unwrap()
     }
 
-    fn trim_ec(&mut self) {
-
-        todo!();
-
-        while let Some(&Type::EmptyCell) = self.array.last() {
-
-            self.array.pop();
-
-        }
-    }
-
     fn move_to(&mut self, expected_index: usize) {
         let str = self.traverse(self.index, expected_index);
 
@@ -755,7 +1065,7 @@ impl Bfasm {
     pub fn set(&mut self, index: usize, item: Type) -> Result<(), BfasmError> {
 
         label!(self.output, "Setting at {}\n", index);
-        // write!(self.output, "Setting {} to {:?}\n", index, item).unwrap();
+        // writeln!(self.output, "Setting {} to {:?}", index, item).unwrap();
 
         self.move_to(index);
 
@@ -919,7 +1229,14 @@ impl Bfasm {
         }
     }
 
-    pub fn clear(&mut self, index: usize) {
+    // marks where the next code came from
+    pub fn source(&mut self, line: usize, column: usize) {
+        if self.output.is_enabled() {
+            self.output.as_mut_bfops().push(BFOp::Source(line, column))
+        }
+    }
+
+    pub fn clear(&mut self, index: usize) -> Result<(), BfasmError> {
 
         label!(self.output, "Clearing {}\n", index);
 
@@ -948,12 +1265,12 @@ impl Bfasm {
                 self.output.code(">>[[-]>>]>[-]\n");
                 self.array[index] = Type::EmptyCell;
 
-                (0..len).for_each(|_| self.array.insert(index, Type::EmptyCell));
+                (1..len).for_each(|_| self.array.insert(index, Type::EmptyCell));
 
                 self.index += len - 1;
             }
             Type::EmptyCell => {
-                panic!()
+                return Err(TypeMismatch(vec![EmptyType::Any], vec![EC]));
             }
             Type::IString(_) | Type::Array(_) => {
 
@@ -970,10 +1287,13 @@ impl Bfasm {
                     self.index = index;
 
                 } else {
-                    panic!()
+                    // a string that can grow has to be the last value on the tape
+                    return Err(TypeMismatch(vec![EEC; rest.len()], Vec::from(rest)));
                 }
             }
         };
+
+        Ok(())
     }
 
     pub fn copy_val(&mut self, index: usize) -> Result<(), BfasmError> {
@@ -1038,8 +1358,8 @@ impl Bfasm {
                     ));
                 }
             }
-            Type::FString(_) | Type::IString(_) | Type::Array(_) | Type::EmptyCell => {
-                unimplemented!()
+            slice @ (Type::FString(_) | Type::IString(_) | Type::Array(_) | Type::EmptyCell) => {
+                return Err(TypeMismatch(vec![EmptyType::U32, EEC, EEC], vec![slice.clone()]));
             }
         }
 
@@ -1096,7 +1416,7 @@ impl Bfasm {
     pub fn input(&mut self, index: usize, input_val: Type) -> Result<(), BfasmError> {
 
         label!(self.output, "Inputing at {}\n", index);
-        // write!(self.output, "Input {:?} at {}\n", input_val, index).unwrap();
+        // writeln!(self.output, "Input {:?} at {}", input_val, index).unwrap();
         self.move_to(index);
 
         match input_val {
@@ -1118,28 +1438,10 @@ impl Bfasm {
             }
 
             Type::IString(str) => {
-                // self.expected_input.push_str(&String::from_utf8(str).unwrap());
+                // a nul char ends the string the same way eof does
+                self.read_to_end(index, str)?;
 
-                self.expected_input
-                    .push_str(&String::from_utf8(str.clone()).unwrap());
                 self.expected_input.push('\0');
-
-                self.output
-                    .code(">>,[[>>]>[->>+<<]>>+<<<<<[[->>+<<]<<]>>,]\n");
-
-                self.output.code(">>[[-<<+>>]>>]>[-<<+>>]<\n");
-
-                let end = &self.array[index..];
-
-                if end == Type::empty_slice(end.len()) {
-                    (0..end.len()).for_each(|_| {
-                        self.array.pop();
-                    });
-
-                    self.array.push(Type::IString(str));
-                }
-
-                self.index += 1;
             }
 
             Type::FString(_) | Type::EmptyCell | Type::Array(_) => {
@@ -1154,6 +1456,117 @@ impl Bfasm {
         self.input(index, Type::IString(Vec::from(str.as_bytes())))
     }
 
+    // strings are read into empty cells so eof reads as 0 whether the interpreter writes 0 or
+    // leaves the cell unchanged, a single char is read with a plain , since it's never read at eof
+    fn read(&self) -> &'static str {
+        match self.eof {
+            // the cell after the read has to be empty
+            EofPolicy::NegativeOne => ",+[->+<]>[-[-<+>]]<",
+            EofPolicy::Error | EofPolicy::Zero | EofPolicy::Unchanged => ",",
+        }
+    }
+
+    // reads until a newline or eof, the newline isn't kept
+    // str is the line as it's read so it only ends with a newline if one was read
+    pub fn input_line(&mut self, index: usize, str: &str) -> Result<(), BfasmError> {
+        label!(self.output, "Inputing a line at {}\n", index);
+
+        let line = str.strip_suffix('\n').unwrap_or(str);
+
+        assert!(!line.contains('\n'));
+
+        self.move_to(index);
+        self.input_slot(index)?;
+
+        self.expected_input.push_str(str);
+
+        // chars are stored 8 cells to the right to leave space to check for the newline
+        let read = self.read();
+
+        write!(self.output, ">>{read}[").unwrap();
+        // check if the char is a newline
+        writeln!(self.output, "[->>+<+<]>[-<+>]>>>++++++++++>>{STR_EQUALS}").unwrap();
+        // if it is clear it and the continue flag
+        self.output.code("<+>[-<-<[-]>>]<\n");
+        // otherwise store the char and read the next one
+        writeln!(
+            self.output,
+            "[->>>>>>>[>>]>[->>+<<]>>+<<<<<[[->>+<<]<<]<<<<<<[->>>>>>>>+<<<<<<<<]{read}>]<]"
+        )
+        .unwrap();
+        // move the string into place
+        self.output
+            .code(">>>>>>>>[[-<<<<<<<<+>>>>>>>>]>>]>[-<<<<<<<<+>>>>>>>>]<<<<<<<\n");
+
+        self.set_input(index, Vec::from(line.as_bytes()));
+
+        Ok(())
+    }
+
+    // reads until eof
+    pub fn input_to_end(&mut self, index: usize, str: &str) -> Result<(), BfasmError> {
+        label!(self.output, "Inputing until eof at {}\n", index);
+
+        self.read_to_end(index, Vec::from(str.as_bytes()))
+    }
+
+    fn read_to_end(&mut self, index: usize, str: Vec<u8>) -> Result<(), BfasmError> {
+        self.move_to(index);
+        self.input_slot(index)?;
+
+        self.expected_input
+            .push_str(&String::from_utf8(str.clone()).unwrap());
+
+        let read = self.read();
+
+        writeln!(self.output, ">>{read}[[>>]>[->>+<<]>>+<<<<<[[->>+<<]<<]>>{read}]").unwrap();
+
+        self.output.code(">>[[-<<+>>]>>]>[-<<+>>]<\n");
+
+        self.set_input(index, str);
+
+        Ok(())
+    }
+
+    // reads exactly len(str) chars
+    pub fn input_exact(&mut self, index: usize, str: &str) -> Result<(), BfasmError> {
+        label!(self.output, "Inputing {} chars at {}\n", str.len(), index);
+
+        self.move_to(index);
+        self.input_slot(index)?;
+
+        self.expected_input.push_str(str);
+
+        let len = str.len();
+
+        // the first char goes on the right
+        writeln!(self.output, "{}{}", ">>".repeat(len), ",<<".repeat(len)).unwrap();
+        writeln!(self.output, "{}>{}>", ">>".repeat(len + 1), "+".repeat(len)).unwrap();
+
+        self.set_input(index, Vec::from(str.as_bytes()));
+
+        Ok(())
+    }
+
+    // strings can only be read into the end of the array
+    fn input_slot(&mut self, index: usize) -> Result<(), BfasmError> {
+        let end = &self.array[index.min(self.array.len())..];
+
+        if end == Type::empty_slice(end.len()) {
+            Ok(())
+        } else {
+            Err(TypeMismatch(vec![EEC], Vec::from(end)))
+        }
+    }
+
+    fn set_input(&mut self, index: usize, str: Vec<u8>) {
+        self.array.truncate(index);
+
+        self.array.push(Type::IString(str));
+
+        self.index += 1;
+    }
+
     pub fn index_str(&mut self, index: usize) -> Result<(), BfasmError> {
         label!(self.output, "Indexing at {index}\n");
         self.move_to(index + 1);
@@ -1255,6 +1668,14 @@ impl Bfasm {
 
         label!(self.output, "Pushing at {index}\n");
 
+        // the char and the cell before it go in front of the string
+        if index < 2 {
+            return Err(TypeMismatch(
+                vec![EEC, EmptyType::Char, EmptyType::IString],
+                Vec::from(self.get_slice(0, index + 1)),
+            ));
+        }
+
         self.move_to(index - 1);
 
         let found = self.get_slice(index - 2, 3);
@@ -1305,6 +1726,14 @@ impl Bfasm {
 
         label!(self.output, "Pushing front at {index}\n");
 
+        // the value and the cell before it go in front of the array
+        if index < 2 {
+            return Err(TypeMismatch(
+                vec![EEC, EmptyType::U32, EmptyType::Array],
+                Vec::from(self.get_slice(0, index + 1)),
+            ));
+        }
+
         self.move_to(index - 1);
 
         let found = self.get_slice(index - 2, 3);
@@ -1335,7 +1764,18 @@ impl Bfasm {
         let found = self.get_slice(index, 3);
 
         if let [EC, Type::U32(val), Type::Array(array)] = found {
-            *val = array[*val as usize];
+            let array_index = *val as usize;
+
+            let ret = match array.get(array_index) {
+                None => {
+                    *val = 0;
+                    Err(BfasmError::OpError(OpError::InvalidArrayIndex(array_index)))
+                }
+                Some(item) => {
+                    *val = *item;
+                    Ok(())
+                }
+            };
 
             // fill the ones
             self.output.code("[->>[>]+[<]<]\n");
@@ -1343,14 +1783,14 @@ impl Bfasm {
             self.output.code(">>[>]>[-<<[<]<+<+>>>[>]>]<<[<]<<\n");
             // put the value back and remove the ones
             self.output.code("[->>>[>]>+<<[<]<<]>>>[->>]<[<<]<-\n");
+
+            ret
         } else {
-            return Err(TypeMismatch(
+            Err(TypeMismatch(
                 vec![EEC, EmptyType::U32, EmptyType::Array],
                 Vec::from(found),
-            ));
+            ))
         }
-
-        Ok(())
     }
 
     // just like the string index
@@ -1363,24 +1803,35 @@ impl Bfasm {
         let found = self.get_slice(index, 3);
 
         if let [Type::Array(array), Type::U32(val), EC] = found {
-            *val = array[array.len() - *val as usize - 1];
+            let back_index = *val as usize;
+
+            let ret = match array.len().checked_sub(back_index + 1) {
+                None => {
+                    *val = 0;
+                    Err(BfasmError::OpError(OpError::InvalidArrayIndex(back_index)))
+                }
+                Some(array_index) => {
+                    *val = array[array_index];
+                    Ok(())
+                }
+            };
 
             // fill ones
-            self.output.code("d[-<<<[<]+[>]>>]\n");
+            self.output.code("[-<<<[<]+[>]>>]\n");
             // grab the indexed value and copy it
             self.output
                 .code("<<<[<]<[->>[>]>>+>+<<<<[<]<]>>[>]>>>\n");
             // put the value back abd remove the ones
             self.output
                 .code("[-<<<<[<]<+>>[>]>>>]<<<<[<]>[>->]>>-\n");
+
+            ret
         } else {
-            return Err(TypeMismatch(
+            Err(TypeMismatch(
                 vec![EmptyType::Array, EmptyType::U32, EEC],
                 Vec::from(found),
-            ));
+            ))
         }
-
-        Ok(())
     }
 
     // just like the string index
@@ -1393,8 +1844,15 @@ impl Bfasm {
         let found = self.get_slice(index, 3);
 
         if let [Type::Array(array), Type::U32(bf_index), Type::U32(val)] = found {
-            let array_index = array.len() - *bf_index as usize - 1;
-            array[array_index] = *val;
+            let back_index = *bf_index as usize;
+
+            let ret = match array.len().checked_sub(back_index + 1) {
+                None => Err(BfasmError::OpError(OpError::InvalidArrayIndex(back_index))),
+                Some(array_index) => {
+                    array[array_index] = *val;
+                    Ok(())
+                }
+            };
 
             self.array[index + 1] = EC;
             self.array[index + 2] = EC;
@@ -1408,15 +1866,48 @@ impl Bfasm {
             self.output.code("[-<<<<[<]<+>>[>]>>>]\n");
 
             // clear the ones
-            self.output.code("<<<<[<]>[>->]>>")
+            self.output.code("<<<<[<]>[>->]>>");
+
+            ret
         } else {
-            return Err(TypeMismatch(
+            Err(TypeMismatch(
                 vec![EmptyType::Array, EmptyType::U32, EEC],
                 Vec::from(found),
-            ));
+            ))
         }
+    }
 
-        Ok(())
+    // turns the index at index into the same item counted from the back of an array
+    // with the length at index + 1
+    pub fn flip_array_index(&mut self, index: usize) -> Result<(), BfasmError> {
+
+        label!(self.output, "Flipping the index at {index}\n");
+
+        self.move_to(index);
+
+        let slice = self.get_slice(index, 2);
+
+        if let [Type::U32(val), Type::U32(len)] = slice {
+            let (val, len) = (*val, *len);
+
+            self.array[index + 1] = EC;
+
+            // take the index and 1 from the length and move it back
+            self.output.code("[->-<]>-[-<+>]<\n");
+
+            if val < len {
+                self.array[index] = Type::U32(len - val - 1);
+                Ok(())
+            } else {
+                self.array[index] = Type::U32(0);
+                Err(BfasmError::OpError(OpError::InvalidArrayIndex(val as usize)))
+            }
+        } else {
+            Err(TypeMismatch(
+                vec![EmptyType::U32, EmptyType::U32],
+                Vec::from(slice),
+            ))
+        }
     }
 
     // Todo Test
@@ -1454,31 +1945,195 @@ impl Bfasm {
         }
     }
 
-    pub fn add_u32(&mut self, index: usize) -> Result<(), BfasmError> {
-
-        label!(self.output, "Adding U32s at {index}\n");
+    // the string ops below mark how far into a string they are with a trail of ones in the empty
+    // cells between the chars, so [<] from the end of the string stops next to the current char
 
-        self.move_to(index);
+    // compares the strings at index and other leaving a bool after the later one
+    pub fn str_equals(&mut self, index: usize, other: usize) -> Result<(), BfasmError> {
 
-        let slice = self.get_slice(index, 2);
+        label!(self.output, "String equals at {index} and {other}\n");
 
-        if let [Type::U32(x), Type::U32(y)] = slice {
-            *x += *y;
-            self.array[index + 1] = EC;
-            self.output.code(">[-<+>]<\n");
-            Ok(())
-        } else {
-            Err(TypeMismatch(
-                vec![EmptyType::U32, EmptyType::U32],
-                Vec::from(slice),
-            ))
-        }
+        self.str_compare(index, other, true)
     }
-    pub fn unsafe_sub_u32(&mut self, index: usize) -> Result<(), BfasmError> {
 
-        label!(self.output, "Unsafe Subtracting U32s at {index}\n");
+    // checks if the string at index starts with the one at other leaving a bool after the later one
+    pub fn str_starts_with(&mut self, index: usize, other: usize) -> Result<(), BfasmError> {
 
-        self.move_to(index);
+        label!(self.output, "String starts with at {index} and {other}\n");
+
+        self.str_compare(index, other, false)
+    }
+
+    fn str_compare(&mut self, index: usize, other: usize, equals: bool) -> Result<(), BfasmError> {
+
+        // the code needs two strings to traverse between
+        if index == other {
+            return Err(TypeMismatch(
+                vec![EmptyType::IString, EmptyType::IString],
+                vec![self.get(index).clone()],
+            ));
+        }
+
+        // the scratch space is after the later string so a char is never moved out of a string
+        // that has to be traversed
+        let end = std::cmp::max(index, other);
+        let start = std::cmp::min(index, other);
+
+        self.move_to(end + 1);
+
+        let found = [self.get(start).clone()]
+            .into_iter()
+            .chain(self.get_slice(end, 9).iter().cloned())
+            .collect::<Vec<_>>();
+
+        if let [Type::FString(first) | Type::IString(first), Type::FString(last) | Type::IString(last), EC, EC, EC, EC, EC, EC, EC, EC] =
+            &found[..]
+        {
+            let (str, other_str) = if index < other { (first, last) } else { (last, first) };
+
+            let res = if equals {
+                str == other_str
+            } else {
+                str.starts_with(other_str)
+            };
+
+            let to_start = self.traverse(end + 1, start + 1);
+            let to_end = self.traverse(start + 1, end + 1);
+
+            // the pointer moves between the first scratch cell and the current chars
+            let start_path = (format!("{to_start}<<<[<]<"), format!(">>[>]>>{to_end}"));
+            let end_path = (String::from("<<<[<]<"), String::from(">>[>]>>"));
+
+            let (str_path, other_path) = if index < other {
+                (start_path, end_path)
+            } else {
+                (end_path, start_path)
+            };
+
+            let fetch_str = trail_fetch(&str_path.0, &str_path.1, &[1]);
+            let fetch_other = trail_fetch(&other_path.0, &other_path.1, &[3, 6]);
+
+            let fill = format!("<<<[<]+[>]>>{to_start}<<<[<]+[>]>>{to_end}");
+
+            // keep going while the chars are equal and the other string hasn't ended
+            write!(self.output, "+[-{fetch_str}{fetch_other}>>>>>{STR_EQUALS}").unwrap();
+            self.output.code(">>>>>[[-]<<<<<[-<+>]>>>>>]<<<<<[-]<\n");
+            writeln!(self.output, "[->+>+<<]>>[-<<+>>]<[-<{fill}>]<]").unwrap();
+
+            // get the result from the chars where it stopped
+            write!(self.output, "{fetch_str}{fetch_other}").unwrap();
+            if equals {
+                writeln!(self.output, ">>>>>{STR_EQUALS}>>>>>[-]<<<<<<").unwrap();
+            } else {
+                self.output.code(">>>[-]<<[-]+>>>>>[[-]<<<<<->>>>>]<<<<<<\n");
+            }
+
+            // clear the trails and move the result into place
+            writeln!(
+                self.output,
+                "<<<[[<]>>->[>]<]>>>{to_start}<<<[[<]>>->[>]<]>>>{to_end}>[-<+>]<"
+            ).unwrap();
+
+            self.array[end + 1] = Type::Bool(res);
+
+            Ok(())
+        } else {
+            Err(TypeMismatch(
+                [EmptyType::IString, EmptyType::IString].into_iter().chain((0..8).map(|_| EEC)).collect(),
+                found,
+            ))
+        }
+    }
+
+    // checks if the string at index contains the char after it
+    pub fn str_contains(&mut self, index: usize) -> Result<(), BfasmError> {
+
+        label!(self.output, "String contains at {index}\n");
+
+        self.str_search(index, true)
+    }
+
+    // replaces the char after the string at index with the index of its first occurrence
+    pub fn str_find(&mut self, index: usize) -> Result<(), BfasmError> {
+
+        label!(self.output, "String find at {index}\n");
+
+        self.str_search(index, false)
+    }
+
+    fn str_search(&mut self, index: usize, contains: bool) -> Result<(), BfasmError> {
+
+        self.move_to(index + 2);
+
+        let found = self.get_slice(index, 10);
+
+        if let [Type::FString(str) | Type::IString(str), Type::Char(char), EC, EC, EC, EC, EC, EC, EC, EC] = found {
+            let char = *char;
+            let position = str.iter().position(|x| *x == char);
+            let len = str.len();
+
+            let to_str = "<<<<[<]<";
+            let from_str = ">>[>]>>>";
+            let fetch_str = trail_fetch(to_str, from_str, &[1, 6]);
+
+            // keep going while the chars are different and the string hasn't ended
+            write!(self.output, "+[-{fetch_str}<[->>>>+>+<<<<<]>>>>>[-<<<<<+>>>>>]>{STR_EQUALS}").unwrap();
+            self.output.code(">+<[[-]>-<]>[-<+>]<>>>>>[[-]<<<<<[-<+>]>>>>>]<<<<<[-]<\n");
+            self.output.code("[->+>+<<]>>[-<<+>>]<[-<<<<<[<]+[>]>>>>]<]\n");
+
+            if contains {
+                // the string has ended unless a char was found
+                writeln!(self.output, "{fetch_str}>[-]>>>>>[[-]<<<<<+>>>>>]<<<<<<").unwrap();
+                self.output.code("<<<<[[<]>>->[>]<]>>>[-]>>[-<<+>>]<<\n");
+
+                self.array[index + 1] = Type::Bool(position.is_some());
+            } else {
+                // count the trail while clearing it
+                self.output.code("<<<<[[<]>>->[>]>>>>+<<<<<]>>>[-]>>[-<<+>>]<<\n");
+
+                self.array[index + 1] = Type::U32(position.unwrap_or(len) as u32);
+            }
+
+            self.index = index + 1;
+
+            match position {
+                Some(_) => Ok(()),
+                None if contains => Ok(()),
+                None => Err(BfasmError::OpError(OpError::CharNotFound(char))),
+            }
+        } else {
+            Err(TypeMismatch(
+                [EmptyType::IString, EmptyType::Char].into_iter().chain((0..8).map(|_| EEC)).collect(),
+                Vec::from(found),
+            ))
+        }
+    }
+
+    pub fn add_u32(&mut self, index: usize) -> Result<(), BfasmError> {
+
+        label!(self.output, "Adding U32s at {index}\n");
+
+        self.move_to(index);
+
+        let slice = self.get_slice(index, 2);
+
+        if let [Type::U32(x), Type::U32(y)] = slice {
+            *x += *y;
+            self.array[index + 1] = EC;
+            self.output.code(">[-<+>]<\n");
+            Ok(())
+        } else {
+            Err(TypeMismatch(
+                vec![EmptyType::U32, EmptyType::U32],
+                Vec::from(slice),
+            ))
+        }
+    }
+    pub fn unsafe_sub_u32(&mut self, index: usize) -> Result<(), BfasmError> {
+
+        label!(self.output, "Unsafe Subtracting U32s at {index}\n");
+
+        self.move_to(index);
 
         let slice = self.get_slice(index, 2);
 
@@ -1509,8 +2164,13 @@ impl Bfasm {
 
         label!(self.output, "Inserting {number} ECs at {index}\n");
 
+        // the cells past the end of the tape are already empty
+        if self.array.len() < index {
+            self.array.resize(index, EC);
+        }
+
         let mut ending_index = self.array.len();
-        while *self.get(ending_index - 1) == EC {
+        while ending_index > index && *self.get(ending_index - 1) == EC {
             ending_index -= 1;
         }
 
@@ -1531,7 +2191,7 @@ impl Bfasm {
                     write!(self.output, "[-{r}+{l}]<[-{r}+{l}]").unwrap();
                 }
                 Type::FString(_) | Type::IString(_) | Type::Array(_) => {
-                    write!(self.output, "[-{r}+{l}]<<<[-{r}+{l}<<]").unwrap();
+                    write!(self.output, "[-{r}+{l}]<<<[[-{r}+{l}]<<]").unwrap();
                 }
                 Type::EmptyCell => {}
             }
@@ -1579,6 +2239,9 @@ impl Bfasm {
             // string would be cleared if match is succesfull
             self.array[index] = EC;
 
+            // the arms are entered on this flag so they can't use it
+            self.array[index + 4] = Type::Bool(true);
+
             let mut errs = None;
 
             // validate the arms
@@ -1593,7 +2256,7 @@ impl Bfasm {
                 // dbg!(match_index, code, &self.array);
 
                 let str = self
-                    .test_arm(code, bunf_index)
+                    .test_arm(code, bunf_index)?
                     .ok_or(BfasmError::InvalidMatchArm(match_index))?;
 
                 if *cond == val {
@@ -1607,7 +2270,7 @@ impl Bfasm {
                     //     oper.exec_instruct(self).expect("Any error should have been caught when validating")
                     // });
 
-                    errs = BfasmOps::full_exec(code, self).unwrap();
+                    errs = BfasmOps::full_exec(code, self)?;
 
                         // .expect("Any error should have been caught when validating");
 
@@ -1618,7 +2281,8 @@ impl Bfasm {
 
                     // dbg!("match over");
 
-                    self.index = bunf_index;
+                    // the next arm starts from the same cell as this one
+                    self.index = bunf_index - 1;
 
                     self.output.enabled(output);
                 }
@@ -1635,6 +2299,7 @@ impl Bfasm {
             self.index = index;
 
             self.array[index] = EC;
+            self.array[index + 4] = EC;
 
             // +++++
             //     >>>>+<<
@@ -1658,11 +2323,12 @@ impl Bfasm {
 
     }
 
+    // None if the arm changes the types of the array
     fn test_arm(
         &mut self,
         code: &[BfasmOps],
         ret_index: usize,
-    ) -> Option<Vec<BFOp>> {
+    ) -> Result<Option<Vec<BFOp>>, BfasmError> {
 
         // dbg!(&self.array, "check start");
 
@@ -1672,6 +2338,12 @@ impl Bfasm {
             index: self.index,
             expected_input: String::new(),
             expected_output: String::new(),
+            eof: self.eof,
+            cells: self.cells,
+            trace: self.trace.clone(),
+            step_limit: self.step_limit,
+            loop_steps: self.loop_steps,
+            verify: self.verify,
         };
 
         // for oper in code {
@@ -1681,13 +2353,21 @@ impl Bfasm {
         for op in code {
             match op.exec_instruct(&mut bfasm) {
                 Ok(()) => {}
-                Err(TypeMismatch(_, _) | BfasmError::InvalidMatchArm(_)) => {
-                    dbg!(op, code, &bfasm.array, "inner err");
-                    return None;
+                Err(
+                    TypeMismatch(_, _)
+                    | BfasmError::InvalidMatchArm(_)
+                    | BfasmError::CellOverflow(_)
+                    | BfasmError::Diverged(_),
+                ) => {
+                    Tracer::trace(&self.trace, || format!("{:?} failed in an arm: {:?}", op, bfasm.array));
+                    return Ok(None);
                 },
                 Err(BfasmError::OpError(_)) => {}
+                Err(err @ BfasmError::StepLimit) => return Err(err),
             }
         }
+
+        self.loop_steps = bfasm.loop_steps;
         // match dbg!(op) {
         //     BfasmOps::CharMatch(ind, _) | BfasmOps::BoolWhile(ind, _) | BfasmOps::BoolIf(ind, _) => {
         //         todo!("Generate the code")
@@ -1710,17 +2390,21 @@ impl Bfasm {
             self.array.push(Type::EmptyCell);
         }
 
-        // todo
-        assert_eq!(&bfasm.array.len(), &self.array.len());
+        // the arm can clear cells off the end
+        while bfasm.array.len() < self.array.len() {
+            bfasm.array.push(Type::EmptyCell);
+        }
 
         if EmptyType::from_vec(&self.array) == EmptyType::from_vec(&bfasm.array) {
             // add better formatting
             let BfasmWriter::BFOps(output, true) = bfasm.output else {unreachable!()};
             // Some(output.replace('\n', "\n  "))
-            Some(output)
+            Ok(Some(output))
         } else {
-            dbg!(&self.array, &bfasm.array, code, "match fail");
-            None
+            Tracer::trace(&self.trace, || {
+                format!("Arm changed the types from {:?} to {:?}", self.array, bfasm.array)
+            });
+            Ok(None)
         }
     }
 
@@ -1740,7 +2424,7 @@ impl Bfasm {
             // correct the array
             self.array[index] = EC;
 
-            let str = self.test_arm(code, index)
+            let str = self.test_arm(code, index)?
                 .ok_or(BfasmError::InvalidMatchArm(0))?;
 
             let mut errs = None;
@@ -1753,7 +2437,7 @@ impl Bfasm {
                 //     oper.exec_instruct(self).expect("Any error should have been caught when validating")
                 // });
 
-                errs = BfasmOps::full_exec(code, self).unwrap();
+                errs = BfasmOps::full_exec(code, self)?;
                     // .expect("Any error should have been caught when validating"); // this panic for a while inside a while
 
                 self.index = index;
@@ -1761,7 +2445,9 @@ impl Bfasm {
                 self.output.enabled(output);
             }
 
-            write!(self.output, "[[-]\n{:?}]\n", str).unwrap();
+            self.output.code("[[-]\n");
+            self.output.extend(str);
+            self.output.code("]\n");
 
             match errs{
                 None => {Ok(())}
@@ -1787,7 +2473,10 @@ impl Bfasm {
         if let Type::Bool(bool) = slice {
             let mut cond = *bool;
 
-            let str = self.test_arm(code, self.index).ok_or(BfasmError::InvalidMatchArm(0))?;
+            let str = self.test_arm(code, self.index)?.ok_or(BfasmError::InvalidMatchArm(0))?;
+
+            // the closing bracket and the code outside of the loops inside it
+            let iteration_steps = 1 + unlooped_steps(&str);
 
             let output = self.output.is_enabled();
             self.output.enabled(false);
@@ -1797,7 +2486,13 @@ impl Bfasm {
             let mut errs = None;
 
             while cond {
-                errs = BfasmOps::full_exec(code, self).unwrap();
+                self.loop_steps += iteration_steps;
+
+                if self.step_limit.is_some_and(|limit| self.loop_steps > limit) {
+                    return Err(BfasmError::StepLimit);
+                }
+
+                errs = BfasmOps::full_exec(code, self)?;
 
                 self.move_to(index);
 
@@ -1807,17 +2502,17 @@ impl Bfasm {
                     break
                 }
 
-                if let Type::Bool(bool) = self.get(index) {
-                    cond = *bool;
-                } else {
-                    panic!();
+                match self.get(index) {
+                    Type::Bool(bool) => cond = *bool,
                     // only tests if it will change the target maybe expand to check all of the array?
-                    // return Err(BfasmError::InvalidMatchArm(0, None));
+                    slice => return Err(TypeMismatch(vec![EmptyType::Bool], vec![slice.clone()])),
                 }
             }
 
             self.output.enabled(output);
-            write!(self.output, "[\n{:?}]\n", str).unwrap();
+            self.output.code("[\n");
+            self.output.extend(str);
+            self.output.code("]\n");
             self.array[index] = EC;
 
             match errs {
@@ -1910,9 +2605,54 @@ impl Bfasm {
     }
 }
 
+// the instructions of the code that are always run, the ones inside of loops might be skipped
+fn unlooped_steps(code: &[BFOp]) -> usize {
+    let mut depth = 0;
+
+    code.iter()
+        .filter(|op| match op {
+            BFOp::OpenBracket => {
+                depth += 1;
+                false
+            }
+            BFOp::CloseBracket => {
+                depth -= 1;
+                false
+            }
+            BFOp::Lable(..) | BFOp::Source(..) | BFOp::Comment(_) => false,
+            _ => depth == 0,
+        })
+        .count()
+}
+
+// the same as equals with [U32, EC, U32, EC, EC] starting from the last cell and ending on the first
+const STR_EQUALS: &str = "+<<[-<<[->]>]>>[<<<+<[>-<[-]]>>>]>-<<[-]<[-<+>]<";
+
+// copies the char at the end of a trail into the target scratch cells using the 7th scratch cell
+// to put it back, starting and ending on the first scratch cell
+fn trail_fetch(to_char: &str, from_char: &str, targets: &[usize]) -> String {
+    let temp = 7;
+
+    let mut add = String::new();
+    let mut pos = 0;
+
+    for target in targets.iter().chain([&temp]) {
+        add.push_str(&">".repeat(target - pos));
+        add.push('+');
+        pos = *target;
+    }
+
+    let l = "<".repeat(temp);
+    let r = ">".repeat(temp);
+
+    format!("{to_char}[-{from_char}{add}{l}{to_char}]{from_char}{r}[-{l}{to_char}+{from_char}{r}]{l}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bfasm::binterp::BFErrorKind;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn bf() {
@@ -1979,6 +2719,48 @@ mod tests {
         assert!(bfasm.test_run().unwrap())
     }
 
+    #[test]
+    fn flip_index_test() {
+        for (val, flipped) in [(0, 2), (2, 0)] {
+            let mut bfasm = Bfasm::default();
+
+            bfasm.set(0, Type::U32(val)).unwrap();
+            bfasm.set(1, Type::U32(3)).unwrap();
+
+            bfasm.flip_array_index(0).unwrap();
+
+            assert_eq!(bfasm.array[0], Type::U32(flipped));
+            assert!(bfasm.test_run().unwrap());
+        }
+
+        let mut bfasm = Bfasm::default();
+
+        bfasm.set(0, Type::U32(3)).unwrap();
+        bfasm.set(1, Type::U32(3)).unwrap();
+
+        assert!(matches!(
+            bfasm.flip_array_index(0),
+            Err(BfasmError::OpError(OpError::InvalidArrayIndex(3)))
+        ));
+
+        // the array ops don't index past the end either
+        for (op, val) in [(BfasmOps::ArrayIndex(0), None), (BfasmOps::ArraySet(0), Some(5))] {
+            let mut bfasm = Bfasm::default();
+
+            bfasm.set(0, Type::Array(vec![1])).unwrap();
+            bfasm.set(1, Type::U32(1)).unwrap();
+
+            if let Some(val) = val {
+                bfasm.set(2, Type::U32(val)).unwrap();
+            }
+
+            assert!(matches!(
+                op.exec_instruct(&mut bfasm),
+                Err(BfasmError::OpError(OpError::InvalidArrayIndex(1)))
+            ));
+        }
+    }
+
     #[test]
     fn insert_test() {
         let mut bfasm = Bfasm::default();
@@ -2001,6 +2783,522 @@ mod tests {
         assert!(bfasm.test_run().unwrap());
     }
 
+    #[test]
+    fn insert_str_test() {
+        let mut bfasm = Bfasm::default();
+
+        bfasm.set(0, Type::from("ab")).unwrap();
+        bfasm.set(3, Type::from('c')).unwrap();
+
+        bfasm.insert_ec(0, 2).unwrap();
+
+        bfasm.set(1, Type::from('d')).unwrap();
+
+        bfasm.str_push(2).unwrap();
+
+        assert!(bfasm.test_run().unwrap());
+    }
+
+    #[test]
+    fn input_line_test() {
+        for str in ["\n", "a\n", "hello world\n"] {
+            let mut bfasm = Bfasm::default();
+
+            bfasm.set(0, Type::U32(3)).unwrap();
+
+            bfasm.input_line(1, str).unwrap();
+            bfasm.input_line(2, "abc").unwrap();
+
+            bfasm.print(0).unwrap();
+
+            assert_eq!(bfasm.expected_input, format!("{str}abc"));
+
+            assert!(bfasm.test_run().unwrap());
+        }
+    }
+
+    #[test]
+    fn eof_test() {
+        for eof in [EofPolicy::Zero, EofPolicy::NegativeOne, EofPolicy::Unchanged] {
+            for str in ["", "a", "hello\nworld"] {
+                let mut bfasm = Bfasm {
+                    eof,
+                    ..Default::default()
+                };
+
+                bfasm.input_line(0, "abc\n").unwrap();
+                bfasm.input_to_end(1, str).unwrap();
+
+                assert!(bfasm.test_run().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn wrapping_cell_test() {
+        let mut bfasm = Bfasm {
+            cells: CellModel::Wrapping8,
+            ..Default::default()
+        };
+
+        BfasmOps::Set(0, Type::U32(255)).exec_instruct(&mut bfasm).unwrap();
+        BfasmOps::Set(1, Type::from("abc")).exec_instruct(&mut bfasm).unwrap();
+        BfasmOps::Set(2, Type::Array(vec![0, 254])).exec_instruct(&mut bfasm).unwrap();
+
+        assert!(bfasm.test_run().unwrap());
+
+        for item in [Type::U32(256), Type::Array(vec![255]), Type::I32(-300)] {
+            let mut bfasm = Bfasm {
+                cells: CellModel::Wrapping8,
+                ..Default::default()
+            };
+
+            assert!(matches!(
+                BfasmOps::Set(0, item).exec_instruct(&mut bfasm),
+                Err(BfasmError::CellOverflow(_))
+            ));
+        }
+
+        let mut interp = BFInterpreter::new(BFOp::from_str("->-+"), String::new());
+        interp.cells = CellModel::Wrapping8;
+
+        interp.run().unwrap();
+        assert_eq!(interp.array, [255, 0]);
+
+        // checked cells can't wrap either way
+        for (code, kind) in [
+            (String::from("-"), BFErrorKind::NegativeCellValue),
+            ("+".repeat(256), BFErrorKind::CellOverflow),
+        ] {
+            let mut interp = BFInterpreter::new(BFOp::from_str(&code), String::new());
+            interp.cells = CellModel::Wrapping8;
+            interp.check_cells = true;
+
+            assert_eq!(interp.run().unwrap_err().kind, kind);
+        }
+
+        // other than a -1 from eof
+        let mut interp = BFInterpreter::new(BFOp::from_str(",+"), String::new());
+        interp.cells = CellModel::Wrapping8;
+        interp.eof = EofPolicy::NegativeOne;
+        interp.check_cells = true;
+
+        interp.run().unwrap();
+        assert_eq!(interp.array, [0]);
+
+        // a full cell the , left alone still can't wrap
+        for cells in [CellModel::Wrapping8, CellModel::U32] {
+            let mut interp = BFInterpreter::new(BFOp::from_str(",+"), String::new());
+            interp.array = vec![cells.max()];
+            interp.cells = cells;
+            interp.eof = EofPolicy::Unchanged;
+            interp.check_cells = true;
+
+            assert_eq!(interp.run().unwrap_err().kind, BFErrorKind::CellOverflow);
+        }
+
+        // the code of an op is checked on every instruction so values that only go out of
+        // range partway through are found
+        for (val, code, reason) in [
+            (255, "+-", "Cell incremented past its largest value"),
+            (0, "-+", "Cell decremented below 0"),
+        ] {
+            let mut bfasm = Bfasm {
+                cells: CellModel::Wrapping8,
+                ..Default::default()
+            };
+
+            bfasm.set(0, Type::U32(val)).unwrap();
+            bfasm.move_to(0);
+            bfasm.output.code(code);
+
+            let Err(BfasmError::Diverged(divergence)) = BfasmOps::MoveTo(1).exec_instruct(&mut bfasm) else {
+                panic!("the op should have diverged")
+            };
+
+            assert!(divergence.reason.starts_with(reason));
+        }
+    }
+
+    #[test]
+    fn peephole_test() {
+        for (code, expected) in [
+            ("+-><[-]", ""),
+            ("+>-<<>", "+>-<"),
+            ("+[-]>><<[-]", "+[-]"),
+            ("+[->+<]L<>[-]>-", "+[->+<]L>-"),
+            ("+[[-]>+-<]", "+[[-]]"),
+        ] {
+            let parse = |code| {
+                BFOp::from_str(code)
+                    .into_iter()
+                    .map(|op| if op == BFOp::Comment('L') { BFOp::Lable(String::new(), None) } else { op })
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(BFOp::peephole(&parse(code)).unwrap(), parse(expected));
+        }
+
+        let mut bfasm = Bfasm::default();
+
+        bfasm.set(0, Type::from("abc")).unwrap();
+        bfasm.set(1, Type::U32(2)).unwrap();
+        bfasm.index_str(0).unwrap();
+        bfasm.set(3, Type::U32(1)).unwrap();
+        bfasm.print(3).unwrap();
+
+        let len = bfasm.output.as_bfops().len();
+
+        bfasm.peephole().unwrap();
+
+        assert!(bfasm.output.as_bfops().len() < len);
+        assert!(bfasm.test_run().unwrap());
+    }
+
+    #[test]
+    fn listing_test() {
+        let mut bfasm = Bfasm::default();
+
+        BfasmOps::Set(0, Type::U32(2)).exec_instruct(&mut bfasm).unwrap();
+        BfasmOps::Clear(0).exec_instruct(&mut bfasm).unwrap();
+
+        assert_eq!(
+            BFOp::as_str(bfasm.output.as_bfops()),
+            "# Setting at 0 (Set)\n++>\n# Clearing 0 (Clear)\n<\n[\n    -\n]\n"
+        );
+    }
+
+    #[test]
+    fn source_test() {
+        let code = vec![
+            BFOp::Source(1, 1),
+            BFOp::Plus,
+            BFOp::Source(2, 5),
+            BFOp::Left,
+        ];
+
+        let mut interp = BFInterpreter::new(code.clone(), String::new());
+
+        assert!(interp.run().is_err());
+        assert_eq!(interp.source(), Some((2, 5)));
+
+        assert_eq!(
+            BFOp::source_map(&code),
+            "{\"mappings\":[{\"start\":0,\"end\":1,\"line\":1,\"column\":1},{\"start\":1,\"end\":2,\"line\":2,\"column\":5}]}"
+        );
+    }
+
+    #[test]
+    fn error_test() {
+        // the error is on the last instruction
+        let mut interp = BFInterpreter::new(BFOp::from_str("+>++<--"), String::new());
+
+        let err = interp.run().unwrap_err();
+
+        assert_eq!(err.kind, BFErrorKind::NegativeCellValue);
+        assert_eq!(err.instruction_index, 6);
+        assert_eq!(err.array_index, 0);
+        assert_eq!(err.cell, 0);
+        assert_eq!(err.window[err.instruction_index - err.window_start], BFOp::Minus);
+
+        assert_eq!(
+            err.to_string(),
+            "Cell decremented below 0 at instruction 6 with the pointer at 0 holding 0 near +>++<-(-)"
+        );
+
+        let err = BFInterpreter::new(BFOp::from_str(">+]"), String::new()).run().unwrap_err();
+
+        assert_eq!(err.kind, BFErrorKind::UnbalancedBrackets);
+        assert_eq!((err.instruction_index, err.array_index, err.cell), (2, 1, 1));
+
+        let err = BFOp::peephole(&BFOp::from_str("[-")).unwrap_err();
+
+        assert_eq!(err.kind, BFErrorKind::UnbalancedBrackets);
+    }
+
+    #[test]
+    fn trace_test() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+
+        let tracer = {
+            let lines = lines.clone();
+            Tracer::new(move |line| lines.lock().unwrap().push(line.to_string()))
+        };
+
+        let mut bfasm = Bfasm {
+            trace: Some(tracer.clone()),
+            ..Default::default()
+        };
+
+        BfasmOps::Set(0, Type::U32(2)).exec_instruct(&mut bfasm).unwrap();
+        BfasmOps::Print(0).exec_instruct(&mut bfasm).unwrap();
+
+        assert_eq!(lines.lock().unwrap().len(), 2);
+        assert_eq!(lines.lock().unwrap()[0], "Set(0, U32(2))\n  [U32(2)] at 1");
+
+        lines.lock().unwrap().clear();
+
+        let mut interp = BFInterpreter::new(BFOp::from_str("+>"), String::new());
+        interp.trace = Some(tracer);
+        interp.run().unwrap();
+
+        assert_eq!(*lines.lock().unwrap(), ["0 Plus at 0 holding 0", "1 Right at 0 holding 1"]);
+    }
+
+    #[test]
+    fn cost_test() {
+        let code = [
+            BfasmOps::Set(0, Type::U32(3)),
+            BfasmOps::BoolIf(
+                1,
+                vec![BfasmOps::Set(2, Type::U32(1)), BfasmOps::Clear(2)],
+            ),
+            BfasmOps::Print(0),
+        ];
+
+        let mut bfasm = Bfasm::default();
+
+        BfasmOps::Set(1, Type::Bool(true)).exec_instruct(&mut bfasm).unwrap();
+
+        let ranges = BfasmOps::exec_ranges(&code, &mut bfasm).unwrap();
+
+        let costs = bfasm.costs(&code, &ranges).unwrap();
+
+        assert_eq!(costs.len(), 3);
+
+        // straight line code runs each instruction once
+        assert_eq!(costs[0].instructions, 6);
+        assert_eq!(costs[0].steps, 6);
+
+        assert!(costs[1].instructions > 0);
+
+        // the body is only run once
+        assert!(costs[1].steps >= costs[1].instructions);
+
+        assert_eq!(costs[2].instructions, 2);
+        assert_eq!(costs[2].steps, 2);
+    }
+
+    #[test]
+    fn profile_test() {
+        let mut labels = ["Looping at 1", "Moving to 3"].into_iter();
+
+        let code = BFOp::from_str("+>L+++[->++[-]<]L>>")
+            .into_iter()
+            .map(|op| {
+                if op == BFOp::Comment('L') {
+                    BFOp::Lable(labels.next().unwrap().to_string(), None)
+                } else {
+                    op
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let profile = BFInterpreter::new(code, String::new()).profile_labels().unwrap();
+
+        assert_eq!(profile.labels.len(), 3);
+        assert_eq!(profile.labels[0].steps, 2);
+        assert_eq!(profile.labels[2].steps, 2);
+        assert_eq!(profile.steps, profile.labels.iter().map(|label| label.steps).sum());
+
+        assert_eq!(profile.loops.len(), 2);
+        assert_eq!(profile.loops[0].iterations, 3);
+        assert_eq!(profile.loops[1].iterations, 6);
+        assert_eq!(profile.max_tape, 4);
+
+        assert!(profile.report().lines().nth(1).unwrap().ends_with("Looping at 1"));
+
+        let folded = profile.folded();
+
+        assert!(folded.contains("Looping at 1;loop@6;loop@11 21\n"));
+        assert_eq!(
+            folded.lines().map(|line| line.rsplit(' ').next().unwrap().parse::<usize>().unwrap()).sum::<usize>(),
+            profile.steps
+        );
+
+        let mut bfasm = Bfasm::default();
+
+        bfasm.set(0, Type::U32(3)).unwrap();
+        bfasm.set(1, Type::from("abc")).unwrap();
+        bfasm.print(0).unwrap();
+
+        let profile = bfasm.profile().unwrap();
+
+        // one label for each op
+        assert_eq!(profile.labels.len(), 4);
+        assert_eq!(profile.labels[1].label, "Setting at 0");
+        assert!(profile.labels[2].steps > profile.labels[3].steps);
+    }
+
+    #[test]
+    fn divergence_test() {
+        let mut bfasm = Bfasm::default();
+
+        BfasmOps::Set(0, Type::U32(3)).exec_instruct(&mut bfasm).unwrap();
+        BfasmOps::Set(1, Type::Bool(true)).exec_instruct(&mut bfasm).unwrap();
+
+        // the array no longer matches the code
+        bfasm.array[0] = Type::U32(4);
+
+        let Err(BfasmError::Diverged(divergence)) = BfasmOps::MoveTo(1).exec_instruct(&mut bfasm) else {
+            panic!("the op should have diverged")
+        };
+
+        assert_eq!(divergence.op.name(), "MoveTo");
+        assert_eq!(divergence.reason, "U32(4) doesn't match the cells before 1");
+        assert_eq!(divergence.actual[..2], [3, 1]);
+        assert_eq!(divergence.expected_pointer, 1);
+        assert_eq!(divergence.actual_pointer, 1);
+        assert_eq!(divergence.code_start + divergence.code.len(), bfasm.output.as_bfops().len());
+        assert!(divergence.to_string().starts_with("MoveTo diverged"));
+
+        // a bad run is reported instead of panicking
+        let mut bfasm = Bfasm::default();
+
+        bfasm.output.code("<");
+
+        let Err(BfasmError::Diverged(divergence)) = BfasmOps::Set(0, Type::U32(1)).exec_instruct(&mut bfasm) else {
+            panic!("the op should have diverged")
+        };
+
+        assert!(divergence.reason.starts_with("Pointer moved left of the first cell"));
+
+        // the code of an op that errs fails so an op checked after it diverges
+        let mut bfasm = Bfasm::default();
+
+        bfasm.set(0, Type::U32(1)).unwrap();
+        bfasm.set(1, Type::U32(2)).unwrap();
+
+        let res = BfasmOps::U32SubUnchecked(0).exec_instruct(&mut bfasm);
+
+        assert!(matches!(res, Err(BfasmError::OpError(OpError::Underflow))));
+        assert!(bfasm.verify);
+
+        let res = BfasmOps::Print(0).exec_instruct(&mut bfasm);
+
+        assert!(matches!(res, Err(BfasmError::Diverged(_))));
+
+        // so a checked run stops at the first op that errs
+        let code = [
+            BfasmOps::Set(0, Type::U32(1)),
+            BfasmOps::Set(1, Type::U32(2)),
+            BfasmOps::U32SubUnchecked(0),
+            BfasmOps::Print(0),
+        ];
+
+        let errs = BfasmOps::full_exec(&code, &mut Bfasm::default()).unwrap();
+
+        assert!(matches!(errs.as_deref(), Some([OpError::Underflow])));
+    }
+
+    #[test]
+    fn step_back_test() {
+        let code = BFOp::from_str(",+>L++[->+<]<.")
+            .into_iter()
+            .map(|op| if op == BFOp::Comment('L') { BFOp::Lable("Adding".to_string(), None) } else { op })
+            .collect::<Vec<_>>();
+
+        let mut interp = BFInterpreter::new(code, "a".to_string());
+        interp.history = Some(Vec::new());
+
+        let start = interp.snapshot();
+
+        interp.run().unwrap();
+
+        let end = interp.snapshot();
+
+        assert!(interp.run_back_to("Adding"));
+        assert_eq!(interp.array[..2], [98, 0]);
+        assert_eq!(interp.array_index, 1);
+        assert_eq!(interp.input_index, 1);
+
+        // replaying gets back to the same place
+        interp.run().unwrap();
+        assert_eq!(interp.snapshot(), end);
+
+        while interp.step_back() {}
+
+        assert_eq!(interp.snapshot(), start);
+        assert!(!interp.run_back_to("Adding"));
+    }
+
+    #[test]
+    fn input_exact_test() {
+        for str in ["", "a", "hello\nworld"] {
+            let mut bfasm = Bfasm::default();
+
+            bfasm.input_exact(0, str).unwrap();
+            bfasm.input_str(1, "ab").unwrap();
+
+            bfasm.get_len(1).unwrap();
+
+            assert!(bfasm.test_run().unwrap());
+        }
+    }
+
+    #[test]
+    fn str_search_test() {
+        for (str, char) in [("hello", 'h'), ("hello", 'l'), ("hello", 'o'), ("hello", 'z'), ("", 'a')] {
+            for contains in [true, false] {
+                let mut bfasm = Bfasm::default();
+
+                bfasm.set(0, Type::from(str)).unwrap();
+                bfasm.set(1, Type::from(char)).unwrap();
+
+                let res = if contains {
+                    bfasm.str_contains(0)
+                } else {
+                    bfasm.str_find(0)
+                };
+
+                assert_eq!(res.is_ok(), contains || str.contains(char));
+
+                assert!(bfasm.test_run().unwrap())
+            }
+        }
+    }
+
+    #[test]
+    fn str_compare_test() {
+        let strs = ["", "a", "ab", "abc", "abd", "b"];
+
+        for str in strs {
+            for other in strs {
+                for equals in [true, false] {
+                    let mut bfasm = Bfasm::default();
+
+                    bfasm.set(0, Type::from(str)).unwrap();
+                    bfasm.set(1, Type::U32(3)).unwrap();
+                    bfasm.set(2, Type::from(other)).unwrap();
+
+                    if equals {
+                        bfasm.str_equals(0, 2).unwrap();
+                    } else {
+                        bfasm.str_starts_with(0, 2).unwrap();
+                    }
+
+                    let expected = if equals { str == other } else { str.starts_with(other) };
+
+                    assert_eq!(bfasm.array[3], Type::Bool(expected));
+
+                    assert!(bfasm.test_run().unwrap())
+                }
+            }
+        }
+
+        // the other string can come first
+        let mut bfasm = Bfasm::default();
+
+        bfasm.set(0, Type::from("ab")).unwrap();
+        bfasm.set(1, Type::from("abc")).unwrap();
+
+        bfasm.str_starts_with(1, 0).unwrap();
+
+        assert_eq!(bfasm.array[2], Type::Bool(true));
+
+        assert!(bfasm.test_run().unwrap())
+    }
+
     #[test]
     fn array_set() {
         let mut bunf = Bfasm::default();
@@ -2042,7 +3340,7 @@ mod tests {
 
         bunf.bool_while(
             0,
-            &vec![
+            &[
                 BfasmOps::Clear(1),
                 BfasmOps::Set(1, Type::U32(1)),
                 BfasmOps::Clear(0),
@@ -2061,7 +3359,7 @@ mod tests {
         bunf.set(0, Type::Bool(true)).unwrap();
         bunf.set(1, Type::I32(-1)).unwrap();
 
-        bunf.bool_if(0, &vec![BfasmOps::Clear(1), BfasmOps::Set(1, Type::I32(1))])
+        bunf.bool_if(0, &[BfasmOps::Clear(1), BfasmOps::Set(1, Type::I32(1))])
             .unwrap();
 
         assert!(bunf.test_run().unwrap())
@@ -2155,7 +3453,7 @@ mod tests {
 
         bunf.index_str(2).unwrap();
 
-        bunf.clear(3);
+        bunf.clear(3).unwrap();
 
         bunf.set(3, Type::from('!')).unwrap();
 
@@ -2213,13 +3511,13 @@ mod tests {
 
         bunf.move_to(0);
 
-        bunf.clear(0);
-        bunf.clear(2);
+        bunf.clear(0).unwrap();
+        bunf.clear(2).unwrap();
 
         bunf.set(0, Type::from(true)).unwrap();
-        bunf.clear(1);
-        bunf.clear(5);
-        bunf.clear(4);
+        bunf.clear(1).unwrap();
+        bunf.clear(5).unwrap();
+        bunf.clear(4).unwrap();
 
         assert!(bunf.test_run().unwrap());
     }
@@ -1,12 +1,203 @@
-#[derive(Debug, Clone)]
-pub enum BFError {
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+use std::io;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+
+// a stream the interpreter reads its input from one byte at a time
+#[derive(Clone)]
+pub struct Reader(pub Arc<Mutex<dyn Read + Send>>);
+
+impl Reader {
+    pub fn new(read: impl Read + Send + 'static) -> Self {
+        Reader(Arc::new(Mutex::new(read)))
+    }
+}
+
+impl Debug for Reader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Reader")
+    }
+}
+
+// a stream the interpreter writes its output to, it is flushed once a run stops
+#[derive(Clone)]
+pub struct Writer(pub Arc<Mutex<dyn Write + Send>>);
+
+impl Writer {
+    pub fn new(write: impl Write + Send + 'static) -> Self {
+        Writer(Arc::new(Mutex::new(write)))
+    }
+
+    // the writer can still be used once the interpreter is done with it
+    pub fn shared<W: Write + Send + 'static>(write: Arc<Mutex<W>>) -> Self {
+        Writer(write)
+    }
+}
+
+impl Debug for Writer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Writer")
+    }
+}
+
+// gets one line for each traced event, nothing is traced unless one is set
+#[derive(Clone)]
+pub struct Tracer(pub Arc<dyn Fn(&str) + Send + Sync>);
+
+impl Tracer {
+    pub fn new(f: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Tracer(Arc::new(f))
+    }
+
+    pub fn stderr() -> Self {
+        Tracer::new(|line| eprintln!("{}", line))
+    }
+
+    // the line is only made if there is a tracer
+    pub fn trace(tracer: &Option<Tracer>, line: impl FnOnce() -> String) {
+        if let Some(tracer) = tracer {
+            (tracer.0)(&line())
+        }
+    }
+}
+
+impl Debug for Tracer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Tracer")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BFErrorKind {
     UnbalancedBrackets,
     NegativeArrayPointer,
     NonASCIIChar,
     InvalidInstructionIndex,
     NegativeCellValue,
+    CellOverflow,
     InputFailed,
     OutputFailed,
+    StepLimit,
+    TapeLimit,
+    // a loop went around without changing anything so it would never end
+    InfiniteLoop,
+    // the snapshot points outside of its tape, the code or the input
+    InvalidSnapshot,
+}
+
+impl Display for BFErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BFErrorKind::UnbalancedBrackets => "Unbalanced brackets",
+            BFErrorKind::NegativeArrayPointer => "Pointer moved left of the first cell",
+            BFErrorKind::NonASCIIChar => "Non ascii char",
+            BFErrorKind::InvalidInstructionIndex => "Invalid instruction index",
+            BFErrorKind::NegativeCellValue => "Cell decremented below 0",
+            BFErrorKind::CellOverflow => "Cell incremented past its largest value",
+            BFErrorKind::InputFailed => "Input ran out",
+            BFErrorKind::OutputFailed => "Output failed",
+            BFErrorKind::StepLimit => "Ran out of steps",
+            BFErrorKind::TapeLimit => "Ran out of tape",
+            BFErrorKind::InfiniteLoop => "Loop can never end",
+            BFErrorKind::InvalidSnapshot => "Snapshot doesn't fit the interpreter",
+        })
+    }
+}
+
+// how many instructions on each side of the error are kept
+const ERROR_WINDOW: usize = 8;
+
+// the state of the interpreter when an error was found
+// errors found without running the code have the pointer and cell left at 0
+#[derive(Debug, Clone)]
+pub struct BFError {
+    pub kind: BFErrorKind,
+    pub instruction_index: usize,
+    pub array_index: usize,
+    pub cell: u32,
+
+    // the instructions around the error starting at window_start
+    pub window: Vec<BFOp>,
+    pub window_start: usize,
+
+    pub source: Option<(usize, usize)>,
+}
+
+impl BFError {
+    fn in_code(kind: BFErrorKind, code: &[BFOp], index: usize) -> Self {
+        let window_start = index.saturating_sub(ERROR_WINDOW).min(code.len());
+
+        BFError {
+            kind,
+            instruction_index: index,
+            array_index: 0,
+            cell: 0,
+            window: code[window_start..(index + ERROR_WINDOW + 1).min(code.len())].to_vec(),
+            window_start,
+            source: BFOp::source_at(code, index),
+        }
+    }
+}
+
+impl Display for BFError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at instruction {}", self.kind, self.instruction_index)?;
+
+        if let Some((line, column)) = self.source {
+            write!(f, " (line {} column {})", line, column)?;
+        }
+
+        write!(f, " with the pointer at {} holding {}", self.array_index, self.cell)?;
+
+        // the failing instruction is put in brackets
+        let window: String = self
+            .window
+            .iter()
+            .enumerate()
+            .filter(|(_, op)| !matches!(op, BFOp::Lable(..) | BFOp::Source(..)))
+            .map(|(index, op)| {
+                let char = BFOp::as_str(std::slice::from_ref(op)).trim().to_string();
+
+                if self.window_start + index == self.instruction_index {
+                    format!("({})", char)
+                } else {
+                    char
+                }
+            })
+            .collect();
+
+        write!(f, " near {}", window)
+    }
+}
+
+impl std::error::Error for BFError {}
+
+// what a read (,) does once the input runs out
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EofPolicy {
+    Error,
+    #[default]
+    Zero,
+    NegativeOne,
+    Unchanged,
+}
+
+// the values a cell can hold
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CellModel {
+    #[default]
+    U32,
+    Wrapping8,
+}
+
+impl CellModel {
+    pub fn max(&self) -> u32 {
+        match self {
+            CellModel::U32 => u32::MAX,
+            CellModel::Wrapping8 => u8::MAX as u32,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -19,11 +210,15 @@ pub enum BFOp {
     Period,
     OpenBracket,
     CloseBracket,
-    Lable,
+    // the text written by label! and the name of the op that wrote it
+    Lable(String, Option<&'static str>),
+    // the line and column of the statement the following code came from
+    Source(usize, usize),
     Comment(char),
 }
 
 impl BFOp {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Vec<BFOp> {
 
         let mut program = Vec::new();
@@ -37,27 +232,310 @@ impl BFOp {
             '.' => program.push(BFOp::Period),
             '[' => program.push(BFOp::OpenBracket),
             ']' => program.push(BFOp::CloseBracket),
-            x => program.push(BFOp::Comment(char)),
+            _ => program.push(BFOp::Comment(char)),
         });
 
         program
     }
 
-    pub fn as_str(code: &[BFOp]) -> String {
-        code.iter().map(|op|{
+    // cancels adjacent inverse ops and removes loops that can never run
+    // labels are kept but ops are merged across them
+    pub fn peephole(code: &[BFOp]) -> Result<Vec<BFOp>, BFError> {
+        let mut output: Vec<BFOp> = Vec::new();
+        let mut index = 0;
+
+        while let Some(op) = code.get(index) {
+            let last = output
+                .iter()
+                .rposition(|op| !matches!(op, BFOp::Lable(..) | BFOp::Source(..) | BFOp::Comment(_)));
+
+            match (op, last.map(|last| &output[last])) {
+                (BFOp::Plus, Some(BFOp::Minus))
+                | (BFOp::Minus, Some(BFOp::Plus))
+                | (BFOp::Left, Some(BFOp::Right))
+                | (BFOp::Right, Some(BFOp::Left)) => {
+                    output.remove(last.unwrap());
+                }
+                // every cell starts at 0 and a loop only ends on a 0
+                (BFOp::OpenBracket, None | Some(BFOp::CloseBracket)) => {
+                    index = equalize_brackets(code, index, 1)?;
+                }
+                _ => output.push(op.clone()),
+            }
+
+            index += 1;
+        }
+
+        Ok(output)
+    }
+
+    // the source position of the instruction at index
+    pub fn source_at(code: &[BFOp], index: usize) -> Option<(usize, usize)> {
+        code[..=index.min(code.len().checked_sub(1)?)].iter().rev().find_map(|op| match op {
+            BFOp::Source(line, column) => Some((*line, *column)),
+            _ => None,
+        })
+    }
+
+    // a json map from ranges of the written code to where they came from
+    // only the eight brainfuck chars are counted
+    pub fn source_map(code: &[BFOp]) -> String {
+        let mut mappings: Vec<(usize, usize, usize, usize)> = Vec::new();
+        let mut index = 0;
+
+        for op in code {
             match op {
-                BFOp::Plus => '+',
-                BFOp::Minus => '-',
-                BFOp::Left => '<',
-                BFOp::Right => '>',
-                BFOp::Comma => ',',
-                BFOp::Period => '.',
-                BFOp::OpenBracket => '[',
-                BFOp::CloseBracket => ']',
-                BFOp::Lable => 'L',
-                BFOp::Comment(x) => *x,
+                BFOp::Source(line, column) => mappings.push((index, index, *line, *column)),
+                BFOp::Lable(..) | BFOp::Comment(_) => {}
+                _ => {
+                    index += 1;
+
+                    if let Some((_, end, _, _)) = mappings.last_mut() {
+                        *end = index;
+                    }
+                }
+            }
+        }
+
+        let mappings: Vec<String> = mappings
+            .iter()
+            // statements that wrote no code
+            .filter(|(start, end, _, _)| start != end)
+            .map(|(start, end, line, column)| {
+                format!("{{\"start\":{},\"end\":{},\"line\":{},\"column\":{}}}", start, end, line, column)
+            })
+            .collect();
+
+        format!("{{\"mappings\":[{}]}}", mappings.join(","))
+    }
+
+    // only the eight instructions
+    pub fn as_code(code: &[BFOp]) -> String {
+        code.iter()
+            .filter_map(|op| match op {
+                BFOp::Plus => Some('+'),
+                BFOp::Minus => Some('-'),
+                BFOp::Left => Some('<'),
+                BFOp::Right => Some('>'),
+                BFOp::Comma => Some(','),
+                BFOp::Period => Some('.'),
+                BFOp::OpenBracket => Some('['),
+                BFOp::CloseBracket => Some(']'),
+                BFOp::Lable(..) | BFOp::Source(..) | BFOp::Comment(_) => None,
+            })
+            .collect()
+    }
+
+    // each label gets its own line and each loop is indented by its depth
+    pub fn as_str(code: &[BFOp]) -> String {
+        let mut listing = String::new();
+        let mut depth = 0;
+
+        let new_line = |listing: &mut String, depth: usize| {
+            if !listing.is_empty() && !listing.ends_with('\n') {
+                listing.push('\n');
+            }
+
+            listing.push_str(&"    ".repeat(depth));
+        };
+
+        code.iter().for_each(|op| match op {
+            BFOp::Lable(text, op) => {
+                new_line(&mut listing, depth);
+
+                match op {
+                    Some(op) => listing.push_str(&format!("# {} ({})\n", text, op)),
+                    None => listing.push_str(&format!("# {}\n", text)),
+                }
+            }
+            BFOp::Source(line, column) => {
+                new_line(&mut listing, depth);
+                listing.push_str(&format!("# at {}:{}\n", line, column));
+            }
+            BFOp::OpenBracket => {
+                new_line(&mut listing, depth);
+                listing.push_str("[\n");
+
+                depth += 1;
+            }
+            BFOp::CloseBracket => {
+                depth = depth.saturating_sub(1);
+
+                new_line(&mut listing, depth);
+                listing.push_str("]\n");
+            }
+            op => {
+                if listing.is_empty() || listing.ends_with('\n') {
+                    new_line(&mut listing, depth);
+                }
+
+                listing.push(match op {
+                    BFOp::Plus => '+',
+                    BFOp::Minus => '-',
+                    BFOp::Left => '<',
+                    BFOp::Right => '>',
+                    BFOp::Comma => ',',
+                    BFOp::Period => '.',
+                    BFOp::Comment(x) => *x,
+                    _ => unreachable!(),
+                })
+            }
+        });
+
+        listing
+    }
+}
+
+// the state a step can change from before it was run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Undo {
+    pub instruction_index: usize,
+    pub array_index: usize,
+    pub array_len: usize,
+    pub cell: u32,
+    pub input_index: usize,
+    pub output_len: usize,
+    pub steps: usize,
+    pub changes: usize,
+    pub loop_start: Option<(usize, usize)>,
+    pub eof_written: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunStatus {
+    Finished,
+    // the step budget ran out before the end of the code
+    Suspended,
+}
+
+// the state of an interpreter without its code
+// written as one line per field with the output as ascii values
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub array: Vec<u32>,
+    pub array_index: usize,
+    pub instruction_index: usize,
+    pub input_index: usize,
+    pub output: String,
+    pub steps: usize,
+}
+
+impl Display for Snapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let list = |vals: Vec<String>| vals.join(" ");
+
+        writeln!(f, "array {}", list(self.array.iter().map(u32::to_string).collect()))?;
+        writeln!(f, "array_index {}", self.array_index)?;
+        writeln!(f, "instruction_index {}", self.instruction_index)?;
+        writeln!(f, "input_index {}", self.input_index)?;
+        writeln!(f, "output {}", list(self.output.bytes().map(|byte| byte.to_string()).collect()))?;
+        writeln!(f, "steps {}", self.steps)
+    }
+}
+
+impl Snapshot {
+    pub fn parse(s: &str) -> Option<Snapshot> {
+        let mut lines = s.lines();
+
+        let mut field = |name: &str| {
+            let line = lines.next()?;
+
+            line.strip_prefix(name)?
+                .split_whitespace()
+                .map(|val| val.parse::<u32>().ok())
+                .collect::<Option<Vec<u32>>>()
+        };
+
+        let single = |vals: Vec<u32>| match vals[..] {
+            [val] => Some(val as usize),
+            _ => None,
+        };
+
+        Some(Snapshot {
+            array: field("array ")?,
+            array_index: single(field("array_index ")?)?,
+            instruction_index: single(field("instruction_index ")?)?,
+            input_index: single(field("input_index ")?)?,
+            output: field("output")?
+                .into_iter()
+                .map(|byte| u8::try_from(byte).ok().filter(u8::is_ascii).map(char::from))
+                .collect::<Option<String>>()?,
+            steps: single(field("steps ")?)?,
+        })
+    }
+}
+
+// the instructions run between a label and the next one
+#[derive(Debug, Clone)]
+pub struct LabelProfile {
+    pub label: String,
+    pub index: usize,
+    pub steps: usize,
+}
+
+// how many times the body of the loop starting at index was run
+#[derive(Debug, Clone)]
+pub struct LoopProfile {
+    pub index: usize,
+    pub label: String,
+    pub iterations: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub labels: Vec<LabelProfile>,
+    pub loops: Vec<LoopProfile>,
+    // the number of cells the tape grew to
+    pub max_tape: usize,
+    pub steps: usize,
+
+    // (label, enclosing loops, steps) for each instruction that was run
+    stacks: Vec<(String, Vec<usize>, usize)>,
+}
+
+impl Profile {
+    // the labels and loops sorted with the hottest first
+    pub fn report(&self) -> String {
+        let mut labels = self.labels.clone();
+        labels.sort_by_key(|label| std::cmp::Reverse(label.steps));
+
+        let mut loops = self.loops.clone();
+        loops.sort_by_key(|lp| std::cmp::Reverse(lp.iterations));
+
+        let mut report = format!("{} steps, {} cells\n", self.steps, self.max_tape);
+
+        labels.iter().filter(|label| label.steps > 0).for_each(|label| {
+            report.push_str(&format!(
+                "{:>10} {:>5.1}% {}\n",
+                label.steps,
+                label.steps as f64 * 100.0 / self.steps.max(1) as f64,
+                label.label
+            ))
+        });
+
+        loops.iter().filter(|lp| lp.iterations > 0).for_each(|lp| {
+            report.push_str(&format!("{:>10} loops at {} in {}\n", lp.iterations, lp.index, lp.label))
+        });
+
+        report
+    }
+
+    // one line per stack in the folded format used by flamegraph tools
+    pub fn folded(&self) -> String {
+        let mut lines: Vec<(String, usize)> = Vec::new();
+
+        self.stacks.iter().for_each(|(label, loops, steps)| {
+            let mut stack = label.replace(';', ",");
+
+            loops.iter().for_each(|index| stack.push_str(&format!(";loop@{}", index)));
+
+            match lines.iter_mut().find(|(line, _)| *line == stack) {
+                Some((_, count)) => *count += steps,
+                None => lines.push((stack, *steps)),
             }
-        }).collect()
+        });
+
+        lines.iter().map(|(stack, steps)| format!("{} {}\n", stack, steps)).collect()
     }
 }
 
@@ -72,6 +550,34 @@ pub struct BFInterpreter {
     pub input: String,
     pub input_index: usize,
     pub output: String,
+
+    pub eof: EofPolicy,
+    pub cells: CellModel,
+    // errors instead of wrapping a cell past either end of its range
+    pub check_cells: bool,
+
+    // the number of instructions run
+    pub steps: usize,
+
+    // traces every instruction run
+    pub trace: Option<Tracer>,
+
+    // used instead of input and output when set
+    pub reader: Option<Reader>,
+    pub writer: Option<Writer>,
+    // errors once more than this many instructions would be run or cells used
+    pub step_limit: Option<usize>,
+    pub tape_limit: Option<usize>,
+
+    // how many instructions have changed the tape, pointer or io
+    pub changes: usize,
+    // the last loop entered and the changes when it was
+    pub loop_start: Option<(usize, usize)>,
+    // the last , hit the end of the input and wrote -1 to the cell
+    pub eof_written: bool,
+
+    // when set every step is recorded so it can be undone
+    pub history: Option<Vec<Undo>>,
 }
 
 impl Default for BFInterpreter {
@@ -95,6 +601,19 @@ impl BFInterpreter {
             input_index: 0,
             input,
             output: String::new(),
+            eof: EofPolicy::default(),
+            cells: CellModel::default(),
+            check_cells: false,
+            steps: 0,
+            trace: None,
+            reader: None,
+            writer: None,
+            step_limit: None,
+            tape_limit: None,
+            changes: 0,
+            loop_start: None,
+            eof_written: false,
+            history: None,
         }
     }
 
@@ -103,18 +622,166 @@ impl BFInterpreter {
             self.exec_one()?;
         }
 
+        self.flush()
+    }
+
+    // runs at most steps instructions
+    pub fn run_for(&mut self, steps: usize) -> Result<RunStatus, BFError> {
+        for _ in 0..steps {
+            if self.instruction_index >= self.instructions.len() {
+                break;
+            }
+
+            self.exec_one()?;
+        }
+
+        self.flush()?;
+
+        if self.instruction_index >= self.instructions.len() {
+            Ok(RunStatus::Finished)
+        } else {
+            Ok(RunStatus::Suspended)
+        }
+    }
+
+    // the output is written without flushing so it is flushed when a run stops
+    fn flush(&self) -> Result<(), BFError> {
+        let Some(writer) = &self.writer else {
+            return Ok(());
+        };
+
+        match writer.0.lock().map(|mut writer| writer.flush()) {
+            Ok(Ok(())) => Ok(()),
+            _ => Err(self.error(BFErrorKind::OutputFailed)),
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            array: self.array.clone(),
+            array_index: self.array_index,
+            instruction_index: self.instruction_index,
+            input_index: self.input_index,
+            output: self.output.clone(),
+            steps: self.steps,
+        }
+    }
+
+    // a reader or writer is left where it is
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), BFError> {
+        let fits = snapshot.array_index < snapshot.array.len()
+            && snapshot.instruction_index <= self.instructions.len()
+            && (self.reader.is_some() || snapshot.input_index <= self.input.len());
+
+        if !fits {
+            return Err(self.error(BFErrorKind::InvalidSnapshot));
+        }
+
+        self.array = snapshot.array.clone();
+        self.array_index = snapshot.array_index;
+        self.instruction_index = snapshot.instruction_index;
+        self.input_index = snapshot.input_index;
+        self.output = snapshot.output.clone();
+        self.steps = snapshot.steps;
+        self.loop_start = None;
+        self.eof_written = false;
+
+        // the recorded steps don't lead to the restored state
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+
         Ok(())
     }
 
+    // runs the rest of the code and returns how many times each instruction was run
+    pub fn profile(&mut self) -> Result<Vec<usize>, BFError> {
+        let mut hits = vec![0; self.instructions.len()];
+
+        while self.instruction_index < self.instructions.len() {
+            hits[self.instruction_index] += 1;
+
+            self.exec_one()?;
+        }
+
+        Ok(hits)
+    }
+
+    // runs the rest of the code and counts the steps of each label and loop
+    pub fn profile_labels(&mut self) -> Result<Profile, BFError> {
+        let hits = self.profile()?;
+
+        let mut label = String::from("start");
+
+        // the code before the first label
+        let mut profile = Profile {
+            labels: vec![LabelProfile { label: label.clone(), index: 0, steps: 0 }],
+            max_tape: self.array.len(),
+            ..Default::default()
+        };
+
+        let mut loops: Vec<usize> = Vec::new();
+
+        for (index, op) in self.instructions.iter().enumerate() {
+            match op {
+                BFOp::Lable(text, _) => {
+                    label = text.clone();
+
+                    profile.labels.push(LabelProfile { label: label.clone(), index, steps: 0 });
+
+                    continue;
+                }
+                BFOp::Source(..) | BFOp::Comment(_) => continue,
+                BFOp::OpenBracket => {
+                    // the ] jumps back once for every time the body is run
+                    let close = equalize_brackets(&self.instructions, index, 1)?;
+
+                    profile.loops.push(LoopProfile { index, label: label.clone(), iterations: hits[close] });
+
+                    loops.push(index);
+                }
+                _ => {}
+            }
+
+            if hits[index] > 0 {
+                profile.steps += hits[index];
+                profile.labels.last_mut().unwrap().steps += hits[index];
+
+                match profile.stacks.last_mut() {
+                    Some((last_label, last_loops, steps)) if *last_label == label && *last_loops == loops => {
+                        *steps += hits[index]
+                    }
+                    _ => profile.stacks.push((label.clone(), loops.clone(), hits[index])),
+                }
+            }
+
+            if *op == BFOp::CloseBracket {
+                loops.pop();
+            }
+        }
+
+        Ok(profile)
+    }
+
+    // where the current instruction came from if the code has source positions
+    pub fn source(&self) -> Option<(usize, usize)> {
+        BFOp::source_at(&self.instructions, self.instruction_index)
+    }
+
     pub fn label_run(&mut self) -> Result<(), BFError> {
 
-        if let Some(BFOp::Lable) = self.instructions.get(self.instruction_index) {
+        // the source of a statement is marked before the label of its first op
+        while let Some(BFOp::Source(..)) = self.instructions.get(self.instruction_index) {
+            self.instruction_index += 1;
+        }
+
+        if let Some(BFOp::Lable(..)) = self.instructions.get(self.instruction_index) {
             self.instruction_index += 1;
         }
 
         while let Some(instruction) = self.instructions.get(self.instruction_index) {
 
-            if *instruction == BFOp::Lable {
+            if let BFOp::Lable(..) = instruction {
                 break
             }
 
@@ -125,27 +792,119 @@ impl BFInterpreter {
     }
 
     pub fn exec_one(&mut self) -> Result<(), BFError> {
+        let undo = self.history.as_ref().map(|_| Undo {
+            instruction_index: self.instruction_index,
+            array_index: self.array_index,
+            array_len: self.array.len(),
+            cell: self.array[self.array_index],
+            input_index: self.input_index,
+            output_len: self.output.len(),
+            steps: self.steps,
+            changes: self.changes,
+            loop_start: self.loop_start,
+            eof_written: self.eof_written,
+        });
+
+        self.step()?;
+
+        if let (Some(history), Some(undo)) = (&mut self.history, undo) {
+            history.push(undo);
+        }
+
+        Ok(())
+    }
+
+    // undoes the last recorded step, streams can't be undone
+    pub fn step_back(&mut self) -> bool {
+        let Some(undo) = self.history.as_mut().and_then(|history| history.pop()) else {
+            return false;
+        };
+
+        // a step only changes the cell the pointer started at
+        self.array.truncate(undo.array_len);
+        self.array[undo.array_index] = undo.cell;
+
+        self.instruction_index = undo.instruction_index;
+        self.array_index = undo.array_index;
+        self.input_index = undo.input_index;
+        self.output.truncate(undo.output_len);
+        self.steps = undo.steps;
+        self.changes = undo.changes;
+        self.loop_start = undo.loop_start;
+        self.eof_written = undo.eof_written;
+
+        true
+    }
+
+    // steps back until the label with the text is the next instruction
+    pub fn run_back_to(&mut self, label: &str) -> bool {
+        while self.step_back() {
+            if let Some(BFOp::Lable(text, _)) = self.instructions.get(self.instruction_index) {
+                if text == label {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn step(&mut self) -> Result<(), BFError> {
 
         let Some(instruction) = self.instructions.get(self.instruction_index)
-            else {return Err(BFError::InvalidInstructionIndex)};
+            else {return Err(self.error(BFErrorKind::InvalidInstructionIndex))};
+
+        if self.step_limit.is_some_and(|limit| self.steps >= limit) {
+            return Err(self.error(BFErrorKind::StepLimit));
+        }
+
+        let changes_state = !matches!(
+            instruction,
+            BFOp::OpenBracket | BFOp::CloseBracket | BFOp::Lable(..) | BFOp::Source(..) | BFOp::Comment(_)
+        );
+
+        if changes_state {
+            self.changes += 1;
+        }
+
+        // only the first change after the , can see the -1 it wrote
+        let after_eof = changes_state && std::mem::take(&mut self.eof_written);
+
+        Tracer::trace(&self.trace, || {
+            format!(
+                "{} {:?} at {} holding {}",
+                self.instruction_index, instruction, self.array_index, self.array[self.array_index]
+            )
+        });
 
         match instruction {
             // increment (>) and decrement (>)
             BFOp::Plus => {
-                self.array[self.array_index] += 1;
+                // a -1 from eof is wrapped back to 0 by the + right after the ,
+                if self.array[self.array_index] < self.cells.max() {
+                    self.array[self.array_index] += 1;
+                } else if !self.check_cells || after_eof {
+                    self.array[self.array_index] = 0;
+                } else {
+                    return Err(self.error(BFErrorKind::CellOverflow))
+                }
             }
             BFOp::Minus => {
                 if self.array[self.array_index] > 0 {
                     self.array[self.array_index] -= 1;
+                } else if self.cells == CellModel::Wrapping8 && !self.check_cells {
+                    self.array[self.array_index] = self.cells.max();
                 } else {
-                    let x = dbg!(self.instruction_index);
-                    dbg!(&self.instructions[x..x + 10]);
-                    return Err(BFError::NegativeCellValue)
+                    return Err(self.error(BFErrorKind::NegativeCellValue))
                 }
             }
 
             // pointer left and right
             BFOp::Right => {
+                if self.tape_limit.is_some_and(|limit| self.array_index + 1 >= limit) {
+                    return Err(self.error(BFErrorKind::TapeLimit));
+                }
+
                 self.array_index += 1;
 
                 if self.array_index == self.array.len() {
@@ -154,7 +913,7 @@ impl BFInterpreter {
             }
             BFOp::Left => {
                 if self.array_index == 0 {
-                    return Err(BFError::NegativeArrayPointer);
+                    return Err(self.error(BFErrorKind::NegativeArrayPointer));
                 };
                 self.array_index -= 1;
             }
@@ -162,11 +921,26 @@ impl BFInterpreter {
             // loop stuff
             BFOp::OpenBracket => {
                 if self.array[self.array_index] == 0 {
-                    self.instruction_index = equalize_brackets(&self.instructions, self.instruction_index, 1)?
+                    self.instruction_index = equalize_brackets(&self.instructions, self.instruction_index, 1)
+                        .map_err(|err| self.error(err.kind))?
+                } else {
+                    self.loop_start = Some((self.instruction_index, self.changes));
                 };
             }
             BFOp::CloseBracket => {
-                self.instruction_index = equalize_brackets(&self.instructions, self.instruction_index, -1)? - 1;
+                let open = equalize_brackets(&self.instructions, self.instruction_index, -1)
+                    .map_err(|err| self.error(err.kind))?;
+
+                // only loops without any loops run inside of them are checked
+                if self.array[self.array_index] != 0 && self.loop_start == Some((open, self.changes)) {
+                    return Err(self.error(BFErrorKind::InfiniteLoop));
+                }
+
+                // the [ is run again to check the cell
+                self.instruction_index = open;
+                self.steps += 1;
+
+                return Ok(());
             }
 
             // input (,) and output (.)
@@ -174,30 +948,99 @@ impl BFInterpreter {
                 // if self.input.is_empty() {
                 //     return Err(BFError::InputFailed);
                 // }
-                let char = self.input.chars().nth(self.input_index).ok_or(BFError::InputFailed)?;
-                if char.is_ascii() {
-                    self.array[self.array_index] = char as u32
-                } else {
-                    return Err(BFError::NonASCIIChar);
+                match self.read_byte()? {
+                    Some(byte) => {
+                        if byte.is_ascii() {
+                            self.array[self.array_index] = byte as u32
+                        } else {
+                            return Err(self.error(BFErrorKind::NonASCIIChar));
+                        }
+                    }
+                    None => match self.eof {
+                        EofPolicy::Error => return Err(self.error(BFErrorKind::InputFailed)),
+                        EofPolicy::Zero => self.array[self.array_index] = 0,
+                        EofPolicy::NegativeOne => {
+                            self.array[self.array_index] = self.cells.max();
+                            self.eof_written = true;
+                        }
+                        EofPolicy::Unchanged => {}
+                    },
                 }
-
-                self.input_index += 1;
             }
             BFOp::Period => {
-                if (self.array[self.array_index] as u8).is_ascii() {
-                    self.output.push(self.array[self.array_index] as u8 as char)
+                let byte = self.array[self.array_index] as u8;
+
+                if !byte.is_ascii() {
+                    return Err(self.error(BFErrorKind::NonASCIIChar));
+                }
+
+                if let Some(writer) = &self.writer {
+                    // a writer that panicked while locked can't be written to
+                    let written = match writer.0.lock() {
+                        Ok(mut writer) => writer.write_all(&[byte]).is_ok(),
+                        Err(_) => false,
+                    };
+
+                    if !written {
+                        return Err(self.error(BFErrorKind::OutputFailed));
+                    }
                 } else {
-                    return Err(BFError::NonASCIIChar);
+                    self.output.push(byte as char)
                 }
             }
-            BFOp::Lable | BFOp::Comment(_) => {}
+            BFOp::Lable(..) | BFOp::Source(..) | BFOp::Comment(_) => {}
         }
 
         self.instruction_index += 1;
+        self.steps += 1;
 
         Ok(())
     }
 
+    // the next byte of input or None once it has run out
+    fn read_byte(&mut self) -> Result<Option<u8>, BFError> {
+        let Some(reader) = &self.reader else {
+            // the input is only indexed by byte so a read doesn't have to walk the string
+            let byte = self.input.as_bytes().get(self.input_index).copied();
+
+            if byte.is_some() {
+                self.input_index += 1;
+            }
+
+            return Ok(byte);
+        };
+
+        let mut byte = [0];
+
+        let read = loop {
+            let Ok(mut reader) = reader.0.lock() else {
+                return Err(self.error(BFErrorKind::InputFailed));
+            };
+
+            match reader.read(&mut byte) {
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                read => break read,
+            }
+        };
+
+        match read {
+            Ok(0) => Ok(None),
+            Ok(_) => {
+                self.input_index += 1;
+                Ok(Some(byte[0]))
+            }
+            Err(_) => Err(self.error(BFErrorKind::InputFailed)),
+        }
+    }
+
+    fn error(&self, kind: BFErrorKind) -> BFError {
+        BFError {
+            array_index: self.array_index,
+            cell: self.array[self.array_index],
+            ..BFError::in_code(kind, &self.instructions, self.instruction_index)
+        }
+    }
+
     // pub fn reset(&mut self) ->(){
     //     self.array = vec![0_u32];
     //     self.array_pointer = 0;
@@ -206,6 +1049,7 @@ impl BFInterpreter {
 }
 
 fn equalize_brackets(program: &[BFOp], mut index: usize, direction: isize) -> Result<usize, BFError> {
+    let start = index;
     let mut depth = 0;
 
     loop {
@@ -220,7 +1064,7 @@ fn equalize_brackets(program: &[BFOp], mut index: usize, direction: isize) -> Re
             Some(_) => {}
 
             None => {
-                return Err(BFError::UnbalancedBrackets);
+                return Err(BFError::in_code(BFErrorKind::UnbalancedBrackets, program, start));
             }
         };
         if depth == 0 {
@@ -230,7 +1074,7 @@ fn equalize_brackets(program: &[BFOp], mut index: usize, direction: isize) -> Re
         index = match index.checked_add_signed(direction) {
             Some(x) => x,
             None => {
-                return Err(BFError::UnbalancedBrackets);
+                return Err(BFError::in_code(BFErrorKind::UnbalancedBrackets, program, start));
             }
         };
     }
@@ -0,0 +1,408 @@
+// random well typed ops are run with their code checked against the interp after each one
+// a failing case is shrunk to the fewest ops that still fail before it is reported
+
+use crate::bfasm::{Bfasm, BfasmOps, BfasmWriter, Type};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+const CASES: u64 = 1000;
+const MAX_OPS: usize = 8;
+const MAX_DEPTH: usize = 2;
+
+// how many ops are tried before giving up on adding one
+const TRIES: usize = 20;
+
+const CHARS: &[u8] = b"abc";
+
+// xorshift so every run checks the same cases
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // a state of 0 would only give 0s
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn char(&mut self) -> u8 {
+        CHARS[self.below(CHARS.len())]
+    }
+}
+
+// a bfasm that only writes code
+fn model() -> Bfasm {
+    Bfasm::new(BfasmWriter::BFOps(Vec::new(), true))
+}
+
+// what an op did to the model
+#[allow(clippy::large_enum_variant)]
+enum Run {
+    Ran(Bfasm),
+    // the op returned an error so it isn't well typed
+    Failed,
+    // an op should return an error instead of panicking so the case is kept to be reported
+    Panicked,
+}
+
+fn try_op(model: &Bfasm, op: &BfasmOps) -> Run {
+    let mut model = model.clone();
+
+    match catch_unwind(AssertUnwindSafe(|| op.exec_instruct(&mut model))) {
+        Ok(Ok(())) => Run::Ran(model),
+        Ok(Err(_)) => Run::Failed,
+        Err(_) => Run::Panicked,
+    }
+}
+
+// the ops after one that panics aren't run
+fn well_typed(ops: &[BfasmOps]) -> bool {
+    let mut model = model();
+
+    for op in ops {
+        match try_op(&model, op) {
+            Run::Ran(next) => model = next,
+            Run::Failed => return false,
+            Run::Panicked => return true,
+        }
+    }
+
+    true
+}
+
+fn random_type(rng: &mut Rng) -> Type {
+    match rng.below(6) {
+        0 => Type::U32(rng.below(5) as u32),
+        1 => Type::I32(rng.below(7) as i32 - 3),
+        2 => Type::Bool(rng.below(2) == 0),
+        3 => Type::Char(rng.char()),
+        4 => Type::FString((0..rng.below(3)).map(|_| rng.char()).collect()),
+        _ => Type::Array((0..rng.below(3)).map(|_| rng.below(4) as u32).collect()),
+    }
+}
+
+// the indexes of the types the predicate matches
+fn find(model: &Bfasm, f: impl Fn(&Type) -> bool) -> Vec<usize> {
+    (0..model.array.len()).filter(|index| f(&model.array[*index])).collect()
+}
+
+fn random_op(rng: &mut Rng, model: &Bfasm, depth: usize) -> BfasmOps {
+    let len = model.array.len() + 2;
+    let (index, other) = (rng.below(len), rng.below(len));
+
+    let nested = if depth < MAX_DEPTH { 3 } else { 0 };
+
+    match rng.below(33 + nested) {
+        0..=3 => BfasmOps::Set(index, random_type(rng)),
+        4 => BfasmOps::MoveTo(index),
+        5 => BfasmOps::MoveType(index, other),
+        6 => BfasmOps::Clear(index),
+        7 => BfasmOps::CopyVal(index),
+        8 => BfasmOps::I32Add(index),
+        9 => BfasmOps::U32Add(index),
+        10 => BfasmOps::U32SubUnchecked(index),
+        11 => BfasmOps::GreaterThan(index),
+        12 => BfasmOps::LessThan(index),
+        13 => BfasmOps::Equals(index),
+        14 => BfasmOps::CharToU32(index),
+        15 => BfasmOps::Print(index),
+        16 => BfasmOps::Len(index),
+        17 => BfasmOps::StrPush(index),
+        18 => BfasmOps::StrPushF(index),
+        19 => BfasmOps::ArrayPush(index),
+        20 => BfasmOps::ArrayPushF(index),
+        21 => BfasmOps::ArrayIndex(index),
+        22 => BfasmOps::ArrayIndexF(index),
+        23 => BfasmOps::ArraySet(index),
+        24 => BfasmOps::StrIndex(index),
+        25 => BfasmOps::Input(index, Type::Char(rng.char())),
+        26 => BfasmOps::StrEquals(index, other),
+        27 => BfasmOps::StrStartsWith(index, other),
+        28 => BfasmOps::StrContains(index),
+        29 => BfasmOps::StrFind(index),
+        30 => BfasmOps::InsertEC(index, 1 + rng.below(2)),
+        31 => BfasmOps::Print(index),
+        32 => BfasmOps::ArrayFlipIndex(index),
+        33 => random_if(rng, model, depth),
+        34 => random_while(rng, model, depth),
+        _ => random_match(rng, model, depth),
+    }
+}
+
+// adds count ops that run on the model
+// the ops end at one that panics
+fn random_ops(rng: &mut Rng, mut model: Bfasm, depth: usize, count: usize) -> (Vec<BfasmOps>, Bfasm) {
+    let mut ops = Vec::new();
+
+    for _ in 0..count {
+        for _ in 0..TRIES {
+            let op = random_op(rng, &model, depth);
+
+            match try_op(&model, &op) {
+                Run::Ran(next) => {
+                    model = next;
+                    ops.push(op);
+                    break;
+                }
+                Run::Failed => {}
+                Run::Panicked => {
+                    ops.push(op);
+                    return (ops, model);
+                }
+            }
+        }
+    }
+
+    (ops, model)
+}
+
+// random ops ending with tail, the cells they fill are cleared so the types end the same as they started
+fn random_block(rng: &mut Rng, start: &Bfasm, depth: usize, tail: Vec<BfasmOps>) -> Vec<BfasmOps> {
+    let count = rng.below(4);
+    let (mut ops, mut end) = random_ops(rng, start.clone(), depth + 1, count);
+
+    for op in tail {
+        if let Run::Ran(next) = try_op(&end, &op) {
+            end = next;
+        }
+
+        ops.push(op);
+    }
+
+    for index in (0..end.array.len()).rev() {
+        let before = start.array.get(index).unwrap_or(&Type::EmptyCell);
+
+        if *before == Type::EmptyCell && end.array[index] != Type::EmptyCell {
+            if let Run::Ran(next) = try_op(&end, &BfasmOps::Clear(index)) {
+                end = next;
+                ops.push(BfasmOps::Clear(index));
+            }
+        }
+    }
+
+    ops
+}
+
+fn random_if(rng: &mut Rng, model: &Bfasm, depth: usize) -> BfasmOps {
+    let bools = find(model, |item| matches!(item, Type::Bool(_)));
+
+    let Some(&index) = bools.get(rng.below(bools.len() + 1)) else {
+        return BfasmOps::Set(model.array.len(), Type::Bool(true));
+    };
+
+    let mut start = model.clone();
+    start.move_to(index);
+    start.array[index] = Type::EmptyCell;
+
+    BfasmOps::BoolIf(index, random_block(rng, &start, depth, Vec::new()))
+}
+
+// the body always ends the loop so it runs at most once
+fn random_while(rng: &mut Rng, model: &Bfasm, depth: usize) -> BfasmOps {
+    let bools = find(model, |item| matches!(item, Type::Bool(_)));
+
+    let Some(&index) = bools.get(rng.below(bools.len() + 1)) else {
+        return BfasmOps::Set(model.array.len(), Type::Bool(true));
+    };
+
+    let mut start = model.clone();
+    start.move_to(index);
+
+    let tail = vec![BfasmOps::Clear(index), BfasmOps::Set(index, Type::Bool(false))];
+
+    BfasmOps::BoolWhile(index, random_block(rng, &start, depth, tail))
+}
+
+fn random_match(rng: &mut Rng, model: &Bfasm, depth: usize) -> BfasmOps {
+    let chars = find(model, |item| matches!(item, Type::Char(_)));
+
+    let Some(&index) = chars.get(rng.below(chars.len() + 1)) else {
+        return BfasmOps::Input(model.array.len(), Type::Char(rng.char()));
+    };
+
+    let mut start = model.clone();
+    start.move_to(index);
+    start.array[index] = Type::EmptyCell;
+    start.index = index + 4;
+
+    let mut arms = Vec::new();
+
+    for char in CHARS {
+        if rng.below(2) == 0 {
+            arms.push((*char, random_block(rng, &start, depth, Vec::new())));
+        }
+    }
+
+    BfasmOps::CharMatch(index, arms)
+}
+
+fn random_case(seed: u64) -> Vec<BfasmOps> {
+    let mut rng = Rng::new(seed);
+    let count = 1 + rng.below(MAX_OPS);
+
+    random_ops(&mut rng, model(), 0, count).0
+}
+
+// why the code of the ops doesn't do what bfasm expects
+fn check(ops: &[BfasmOps]) -> Option<String> {
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        let mut bfasm = Bfasm::default();
+
+        match BfasmOps::full_exec(ops, &mut bfasm) {
+            Ok(None) => {}
+            Ok(Some(errs)) => return Some(format!("{:?}", errs)),
+            Err(err) => return Some(err.to_string()),
+        }
+
+        // the whole code run from the start
+        match bfasm.test_run() {
+            Ok(true) => None,
+            Ok(false) => Some("The full run doesn't match".to_string()),
+            Err(err) => Some(err.to_string()),
+        }
+    }));
+
+    res.unwrap_or_else(|_| Some("Panicked".to_string()))
+}
+
+// every case with one op removed, including ops inside blocks and whole match arms
+fn smaller(ops: &[BfasmOps]) -> Vec<Vec<BfasmOps>> {
+    let mut cases = Vec::new();
+
+    for (index, op) in ops.iter().enumerate() {
+        let mut removed = ops.to_vec();
+        removed.remove(index);
+        cases.push(removed);
+
+        let inner: Vec<BfasmOps> = match op {
+            BfasmOps::BoolIf(cond, code) => {
+                smaller(code).into_iter().map(|code| BfasmOps::BoolIf(*cond, code)).collect()
+            }
+            BfasmOps::BoolWhile(cond, code) => {
+                smaller(code).into_iter().map(|code| BfasmOps::BoolWhile(*cond, code)).collect()
+            }
+            BfasmOps::CharMatch(char, arms) => (0..arms.len())
+                .flat_map(|arm| {
+                    let mut fewer = arms.clone();
+                    fewer.remove(arm);
+
+                    let shorter = smaller(&arms[arm].1).into_iter().map(move |code| {
+                        let mut arms = arms.clone();
+                        arms[arm].1 = code;
+                        arms
+                    });
+
+                    std::iter::once(fewer).chain(shorter)
+                })
+                .map(|arms| BfasmOps::CharMatch(*char, arms))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        for op in inner {
+            let mut case = ops.to_vec();
+            case[index] = op;
+            cases.push(case);
+        }
+    }
+
+    cases
+}
+
+fn shrink(mut ops: Vec<BfasmOps>, fails: impl Fn(&[BfasmOps]) -> bool) -> Vec<BfasmOps> {
+    'shrink: loop {
+        for case in smaller(&ops) {
+            if well_typed(&case) && fails(&case) {
+                ops = case;
+                continue 'shrink;
+            }
+        }
+
+        return ops;
+    }
+}
+
+fn count_nested(ops: &[BfasmOps], name: &str) -> usize {
+    ops.iter()
+        .map(|op| {
+            let inner = match op {
+                BfasmOps::BoolIf(_, code) | BfasmOps::BoolWhile(_, code) => count_nested(code, name),
+                BfasmOps::CharMatch(_, arms) => arms.iter().map(|(_, code)| count_nested(code, name)).sum(),
+                _ => 0,
+            };
+
+            inner + (op.name() == name) as usize
+        })
+        .sum()
+}
+
+#[test]
+fn fuzz_test() {
+    let cases: Vec<Vec<BfasmOps>> = (0..CASES).map(random_case).collect();
+
+    for (seed, ops) in cases.iter().enumerate() {
+        if check(ops).is_some() {
+            let ops = shrink(ops.clone(), |ops| check(ops).is_some());
+
+            panic!("Case {} failed with {}\n{:#?}", seed, check(&ops).unwrap(), ops);
+        }
+    }
+
+    // the cases reach into blocks
+    for name in ["BoolIf", "BoolWhile", "CharMatch"] {
+        assert!(cases.iter().map(|ops| count_nested(ops, name)).sum::<usize>() > 5, "Too few {}", name);
+    }
+}
+
+#[test]
+fn shrink_test() {
+    let printed = |ops: &[BfasmOps]| {
+        let mut model = model();
+        ops.iter().for_each(|op| op.exec_instruct(&mut model).unwrap());
+        !model.expected_output.is_empty()
+    };
+
+    let ops = (0..CASES).map(random_case).find(|ops| ops.len() > 4 && printed(ops)).unwrap();
+
+    // only the print and the value it needs are left
+    let ops = shrink(ops, printed);
+
+    assert_eq!(ops.len(), 2);
+    assert!(printed(&ops));
+}
+
+// cases the fuzzer shrunk
+#[test]
+fn regression_test() {
+    use BfasmOps::*;
+
+    // an arm can't use the flag the match enters it on
+    let arm = vec![Set(7, Type::Bool(true)), Clear(7)];
+    assert!(!well_typed(&[Input(3, Type::Char(b'b')), CharMatch(3, vec![(b'b', arm), (b'c', vec![])])]));
+
+    // an arm can clear cells off the end of the array
+    let ops = [Set(1, Type::Bool(true)), BoolIf(1, vec![Set(1, Type::Array(vec![0])), Clear(1)])];
+    assert!(well_typed(&ops));
+    assert_eq!(check(&ops), None);
+
+    // a string can't be compared with itself
+    assert!(!well_typed(&[Set(0, Type::from("b")), StrStartsWith(0, 0)]));
+
+    // these ops panicked on the wrong types instead of returning an error
+    assert!(!well_typed(&[ArrayPushF(1)]));
+    assert!(!well_typed(&[StrPush(0)]));
+    assert!(!well_typed(&[Clear(0)]));
+    assert!(!well_typed(&[Set(0, Type::from("a")), CopyVal(0)]));
+
+    let ops = [Set(0, Type::U32(1)), InsertEC(3, 1)];
+    assert!(well_typed(&ops));
+    assert_eq!(check(&ops), None);
+}
@@ -1,11 +1,21 @@
 mod bfasm;
+// a bunf program written as rust, it is only built for the tests to run natively as a reference
+#[cfg(test)]
 mod program;
 
+use std::fmt::{Display, Formatter};
 use std::str::Chars;
-use crate::bfasm::{Bfasm, BfasmOps, EmptyType, OpError, Type};
+use std::{fmt, mem};
+use crate::bfasm::Bfasm;
+use crate::bfasm::binterp::BFInterpreter;
+
+pub use crate::bfasm::{BfasmError, BfasmOps, Divergence, EmptyType, OpCost, OpError, Type};
+pub use crate::bfasm::binterp::{
+    BFError, BFErrorKind, BFOp, CellModel, EofPolicy, Reader, RunStatus, Snapshot, Tracer, Writer,
+};
 
 #[derive(PartialEq, Debug, Clone)]
-pub enum Function {
+pub(crate) enum Function {
     IndexStr(String, Value),
     Index(String, Value),
     IndexSet(String, Value, Value),
@@ -17,7 +27,14 @@ pub enum Function {
     LessThan(Value, Value),
     Len(String),
     Push(String, Value),
+    PushFront(String, Value),
+    StartsWith(String, Value),
+    Contains(String, Value),
+    Find(String, Value),
     InputStr,
+    ReadLine,
+    ReadToEnd,
+    NewString,
     NewArray,
     InputU32,
     PrintU32(Value),
@@ -38,38 +55,32 @@ impl Function {
             Function::LessThan(_, _) => Some(EmptyType::Bool),
             Function::Len(_) => Some(EmptyType::U32),
             Function::Push(_, _) => None,
+            Function::PushFront(_, _) => None,
+            Function::StartsWith(_, _) => Some(EmptyType::Bool),
+            Function::Contains(_, _) => Some(EmptyType::Bool),
+            Function::Find(_, _) => Some(EmptyType::U32),
             Function::InputStr => Some(EmptyType::IString),
+            Function::ReadLine => Some(EmptyType::IString),
+            Function::ReadToEnd => Some(EmptyType::IString),
+            Function::NewString => Some(EmptyType::FString),
             Function::NewArray => Some(EmptyType::Array),
-            Function::InputU32 => Some(EmptyType::Char),
+            Function::InputU32 => Some(EmptyType::U32),
             Function::PrintU32(_) => None,
             Function::CloneU32(_) => Some(EmptyType::U32),
         }
     }
-    fn parens_call(fn_name: &str, value: Option<Value>) -> Self {
-        match fn_name {
-            "input_str" => {
-                assert_eq!(value, None);
-                Function::InputStr
-            }
-            "new_array" => {
-                assert_eq!(value, None);
-                Function::NewArray
-            }
-            "input_u32" => {
-                assert_eq!(value, None);
-                Function::InputU32
-            }
-            "print_u32" => {
-                if let Some(val) = value {
-                    Function::PrintU32(val)
-                } else {
-                    panic!()
-                }
-            }
-            _ => {
-                panic!("Unknown function name: {}", fn_name)
-            }
-        }
+    // None if there isn't a function with the name that takes the value
+    fn parens_call(fn_name: &str, value: Option<Value>) -> Option<Self> {
+        Some(match (fn_name, value) {
+            ("input_str", None) => Function::InputStr,
+            ("read_line", None) => Function::ReadLine,
+            ("read_to_end", None) => Function::ReadToEnd,
+            ("new_array", None) => Function::NewArray,
+            ("input_u32", None) => Function::InputU32,
+            ("String::new", None) => Function::NewString,
+            ("print_u32", Some(val)) => Function::PrintU32(val),
+            _ => return None,
+        })
     }
 
     // amount of space after the variable needed for the function
@@ -96,25 +107,16 @@ impl Function {
     // }
 }
 #[derive(PartialEq, Debug, Clone)]
-pub enum Value {
+pub(crate) enum Value {
     Func(Box<Function>),
     Static(Type),
 }
 
-impl Value {
-    fn bftype(&self) -> EmptyType {
-        match self {
-            Value::Func(func) => (**func).return_type().unwrap(),
-            Value::Static(bftype) => EmptyType::from(bftype),
-        }
-    }
-}
-
 #[derive(Debug)]
-pub enum Statement {
-    If(Value, Vec<Statement>),
-    Match(Value, Vec<(Type, Vec<Statement>)>),
-    While(Value, Vec<Statement>),
+pub(crate) enum Statement {
+    If(Value, Block),
+    Match(Value, Vec<(Type, Block)>),
+    While(Value, Block),
     Function(Function),
 }
 
@@ -148,10 +150,16 @@ pub enum Statement {
 
 type Variable = (String, EmptyType, usize);
 
-type AnnotatedBlock = (Vec<AnnotatedStatement>, Vec<Variable>);
+// the line and column of a token
+type Span = (usize, usize);
+
+// each statement with where it starts in the source if that is known
+type Block = Vec<(Option<Span>, Statement)>;
+
+type AnnotatedBlock = (Vec<(Option<Span>, AnnotatedStatement)>, Vec<Variable>);
 
 #[derive(Debug)]
-pub enum AnnotatedStatement {
+pub(crate) enum AnnotatedStatement {
     If(Value, AnnotatedBlock),
     Match(Value, Vec<(Type, AnnotatedBlock)>),
     While(Value, AnnotatedBlock),
@@ -187,7 +195,7 @@ pub enum AnnotatedStatement {
 // }
 
 #[derive(Debug, PartialEq)]
-pub enum Token {
+pub(crate) enum Token {
     Let,
     Equal,
     // DoubleEqual,
@@ -213,6 +221,8 @@ pub enum Token {
     Plus,
     Minus,
     Mut,
+    Colon,
+    Ampersand,
 }
 
 // impl From<&Token> for String {
@@ -249,17 +259,28 @@ pub enum Token {
 // struct TokenizeError(usize, Option<char>, String);
 
 // either returns the tokens or the point of failure
-pub fn tokenize(code: &str) -> Option<Vec<Token>> {
+#[cfg(test)]
+pub(crate) fn tokenize(code: &str) -> Option<Vec<Token>> {
+    tokenize_spans(code).map(|(tokens, _)| tokens)
+}
+
+// also returns the line and column each token starts at
+pub(crate) fn tokenize_spans(code: &str) -> Option<(Vec<Token>, Vec<Span>)> {
     let mut char_iter = code.chars().enumerate();
 
     let mut tokens = Vec::new();
+    let mut starts = Vec::new();
 
     loop {
-        let Some((str, (_, char))) = next_word(&mut char_iter) else {
-            return Some(tokens);
+        let Some((str, (char_index, char))) = next_word(&mut char_iter) else {
+            let spans = index_spans(code, &starts);
+
+            return Some((tokens, spans));
         };
 
         if !str.is_empty() {
+            starts.push(char_index - str.chars().count());
+
             tokens.push(match str.as_str() {
                 "let" => Token::Let,
                 "while" => Token::While,
@@ -270,7 +291,16 @@ pub fn tokenize(code: &str) -> Option<Vec<Token>> {
                 str => Token::Name(String::from(str)),
             });
         }
+
+        // line comments are skipped
+        if char == '/' && matches!(char_iter.clone().next(), Some((_, '/'))) {
+            char_iter.find(|(_, char)| *char == '\n');
+            continue;
+        }
+
         if char != ' ' && char != '\n' && char != '\r' {
+            starts.push(char_index);
+
             tokens.push(match char {
                 '=' => Token::Equal,
                 ';' => Token::SemiColon,
@@ -298,17 +328,57 @@ pub fn tokenize(code: &str) -> Option<Vec<Token>> {
 
                     Token::Name(format!("'{}'", quote))
                 }
+                '"' => {
+                    let mut quote = String::new();
+
+                    loop {
+                        let source_quote = char_iter.next()?.1;
+
+                        if source_quote == '"' {
+                            break;
+                        }
+
+                        quote.push(source_quote);
+                    }
+
+                    Token::Name(format!("\"{}\"", quote))
+                }
                 '+' => Token::Plus,
                 '-' => Token::Minus,
                 '.' => Token::Dot,
-                _val => {
-                    panic!("Unknown non-alphanumeric char: {_val}")
-                }
+                ':' => Token::Colon,
+                '&' => Token::Ampersand,
+                _ => return None,
             });
         }
     }
 }
 
+// the line and column of each char index, the indexes have to be in order
+fn index_spans(code: &str, indexes: &[usize]) -> Vec<Span> {
+    let mut chars = code.chars();
+    let (mut line, mut column) = (1, 1);
+    let mut current = 0;
+
+    indexes
+        .iter()
+        .map(|&index| {
+            for char in chars.by_ref().take(index - current) {
+                if char == '\n' {
+                    line += 1;
+                    column = 1;
+                } else {
+                    column += 1;
+                }
+            }
+
+            current = index;
+
+            (line, column)
+        })
+        .collect()
+}
+
 // returns an alphanumeric string and the non-alphanumeric or None if the iter was ended before a
 // non-alphanumeric char was found
 fn next_word(iter: &mut std::iter::Enumerate<Chars>) -> Option<(String, (usize, char))> {
@@ -325,69 +395,94 @@ fn next_word(iter: &mut std::iter::Enumerate<Chars>) -> Option<(String, (usize,
     None
 }
 
-pub fn tokens_to_statements(tokens: &[Token]) -> Result<Vec<Statement>, Option<usize>> {
+#[cfg(test)]
+pub(crate) fn tokens_to_statements(tokens: &[Token]) -> Result<Block, Option<usize>> {
+    statements_with_spans(tokens, None)
+}
+
+// gives each statement the position of its first token
+pub(crate) fn tokens_to_spanned_statements(tokens: &[Token], spans: &[Span]) -> Result<Block, Option<usize>> {
+    statements_with_spans(tokens, Some(spans))
+}
+
+fn statements_with_spans(tokens: &[Token], spans: Option<&[Span]>) -> Result<Block, Option<usize>> {
     use Token as T;
 
     let mut index = 0;
 
-    let mut statements = Vec::new();
+    let mut block = Vec::new();
+
+    // the spans of the tokens in a block
+    let block_spans = |start: usize, end: usize| spans.map(|spans| &spans[start..end]);
 
     loop {
         let Some(current_token) = tokens.get(index) else {
-            return Ok(statements);
+            return Ok(block);
+        };
+
+        let span = spans.map(|spans| spans[index]);
+
+        // the statements made from the tokens of one statement
+        let mut statements = Vec::new();
+
+        // a statement that can't be parsed fails at its first token
+        let start = index;
+
+        let value = |tokens: &[Token]| tokens_to_value(tokens).ok_or(Some(start));
+        let find = |index: usize, token: Token| find_token(tokens, index, &token).ok_or(Some(start));
+        let balanced = |index: usize| find_next_balanced(tokens, index).ok_or(Some(start));
+
+        // the statements between the braces, a failure in them is moved to where it is in these tokens
+        let sub_block = |open: usize, close: usize| {
+            statements_with_spans(&tokens[open + 1..close], block_spans(open + 1, close))
+                .map_err(|err| err.map(|index| index + open + 1))
         };
 
         match current_token {
             Token::Let => {
                 // todo combine
 
-                if let [T::Let, T::Name(ref var), T::Equal] = &tokens[index..index + 3] {
+                if let Some([T::Let, T::Name(ref var), T::Equal]) = tokens.get(index..index + 3) {
                     index += 3;
                     let starting_index = index;
 
-                    while tokens[index] != Token::SemiColon {
-                        index += 1;
-                    }
+                    index = find(index, T::SemiColon)?;
 
                     statements.push(Statement::Function(Function::Assign(
                         var.clone(),
-                        tokens_to_value(&tokens[starting_index..index]).unwrap(),
+                        value(&tokens[starting_index..index])?,
                     )));
 
                     index += 1;
-                } else if let [T::Let, T::Mut, T::Name(ref var), T::Equal] =
-                    &tokens[index..index + 4]
+                } else if let Some([T::Let, T::Mut, T::Name(ref var), T::Equal]) =
+                    tokens.get(index..index + 4)
                 {
                     index += 4;
                     let starting_index = index;
 
-                    while tokens[index] != Token::SemiColon {
-                        index += 1;
-                    }
+                    index = find(index, T::SemiColon)?;
 
                     statements.push(Statement::Function(Function::Assign(
                         var.clone(),
-                        tokens_to_value(&tokens[starting_index..index]).unwrap(),
+                        value(&tokens[starting_index..index])?,
                     )));
 
                     index += 1;
+                } else {
+                    return Err(Some(start));
                 }
             }
 
             Token::While => {
                 let starting_index = index + 1; // move past the while token
 
-                while tokens[index] != Token::OpenBrace {
-                    index += 1
-                }
-
-                let block_index = index;
+                let block_index = find(index, T::OpenBrace)?;
 
-                index = find_next_balanced(tokens, index);
+                index = balanced(block_index)?;
 
                 statements.push(Statement::While(
-                    tokens_to_value(&tokens[starting_index..block_index]).unwrap(),
-                    tokens_to_statements(&tokens[block_index + 1..index]).unwrap(), // remove the ending brace
+                    value(&tokens[starting_index..block_index])?,
+                    sub_block(block_index, index)?, // remove the ending brace
                 ));
 
                 index += 1;
@@ -398,44 +493,51 @@ pub fn tokens_to_statements(tokens: &[Token]) -> Result<Vec<Statement>, Option<u
             Token::Name(ref var) => {
                 index += 1;
 
-                // ex: x += 1;
-                if let [oper @ (T::Plus | T::Minus), T::Equal, T::Name(ref val), T::SemiColon] =
-                    &tokens[index..index + 4]
+                // ex: x += 1; or x += &y;
+                // adding to a string is changed to a push_str once its type is known
+                if let Some([T::Plus, T::Equal]) = tokens.get(index..index + 2) {
+                    index += 2;
+                    let starting_index = index;
+
+                    index = find(index, T::SemiColon)?;
+
+                    statements.push(Statement::Function(Function::Assign(
+                        var.clone(),
+                        Value::Func(Box::new(Function::Add(
+                            Value::Func(Box::new(Function::CloneU32(String::from(var)))),
+                            value(&tokens[starting_index..index])?,
+                        ))),
+                    )));
+
+                    index += 1;
+
+                // ex: x -= 1;
+                } else if let Some([T::Minus, T::Equal, T::Name(ref val), T::SemiColon]) =
+                    tokens.get(index..index + 4)
                 {
                     statements.push(Statement::Function(Function::Assign(
                         var.clone(),
-                        Value::Func(if *oper == T::Plus {
-                            Box::new(Function::Add(
-                                Value::Func(Box::new(Function::CloneU32(String::from(var)))),
-                                Value::Static(str_to_type(val).unwrap()),
-                            ))
-                        } else if *oper == T::Minus {
-                            Box::new(Function::Subtract(
-                                Value::Func(Box::new(Function::CloneU32(String::from(var)))),
-                                Value::Static(str_to_type(val).unwrap()),
-                            ))
-                        } else {
-                            unreachable!()
-                        }),
+                        Value::Func(Box::new(Function::Subtract(
+                            Value::Func(Box::new(Function::CloneU32(String::from(var)))),
+                            Value::Static(str_to_type(val).ok_or(Some(start))?),
+                        ))),
                     )));
 
                     index += 4;
 
                     // ex: x[1] = ..;
-                } else if let [T::OpenBracket, T::Name(ref var_index), T::CloseBracket, T::Equal] =
-                    &tokens[index..index + 4]
+                } else if let Some([T::OpenBracket, T::Name(ref var_index), T::CloseBracket, T::Equal]) =
+                    tokens.get(index..index + 4)
                 {
                     index += 4;
                     let starting_index = index;
 
-                    while Token::SemiColon != tokens[index] {
-                        index += 1;
-                    }
+                    index = find(index, T::SemiColon)?;
 
                     statements.push(Statement::Function(Function::IndexSet(
                         var.clone(),
                         str_to_value(var_index),
-                        tokens_to_value(&tokens[starting_index..index]).unwrap(),
+                        value(&tokens[starting_index..index])?,
                     )));
 
                     index += 1;
@@ -447,16 +549,16 @@ pub fn tokens_to_statements(tokens: &[Token]) -> Result<Vec<Statement>, Option<u
                     index += 5;
                     let starting_index = index;
 
-                    while Token::SemiColon != tokens[index] {
-                        index += 1;
-                    }
+                    index = find(index, T::SemiColon)?;
+
+                    let val = value(&tokens[starting_index..index])?;
 
                     statements.push(Statement::Function(Function::IndexSet(
                         var.clone(),
                         str_to_value(var_index),
                         Value::Func(Box::from(if *oper == T::Plus {
                             Function::Add(
-                                tokens_to_value(&tokens[starting_index..index]).unwrap(),
+                                val,
                                 Value::Func(Box::new(Function::Index(
                                     var.clone(),
                                     str_to_value(var_index),
@@ -464,11 +566,11 @@ pub fn tokens_to_statements(tokens: &[Token]) -> Result<Vec<Statement>, Option<u
                             )
                         } else if *oper == T::Minus {
                             Function::Subtract(
-                                tokens_to_value(&tokens[starting_index..index]).unwrap(),
                                 Value::Func(Box::from(Function::Index(
                                     var.clone(),
                                     str_to_value(var_index),
                                 ))),
+                                val,
                             )
                         } else {
                             unreachable!()
@@ -476,36 +578,45 @@ pub fn tokens_to_statements(tokens: &[Token]) -> Result<Vec<Statement>, Option<u
                     )));
 
                     index += 1;
+                // ex: x.push_str(&y);
+                } else if matches!(
+                    tokens.get(index..index + 3),
+                    Some([T::Dot, T::Name(func_name), T::OpenParens]) if func_name == "push_str"
+                ) {
+                    let parens_index = balanced(index + 2)?;
+
+                    statements.append(
+                        &mut push_str_statements(var, value(&tokens[index + 3..parens_index])?)
+                            .ok_or(Some(start))?,
+                    );
+
+                    index = parens_index + 2; // move past the parens and semicolon
                 // ex: x. or x(
-                } else if let T::Dot | T::OpenParens = &tokens[index] {
+                } else if let Some(T::Dot | T::OpenParens) = tokens.get(index) {
                     let starting_index = index;
 
-                    while tokens[index] != Token::SemiColon {
-                        index += 1;
-                    }
+                    index = find(index, T::SemiColon)?;
 
                     let token = &tokens[starting_index - 1..index];
 
                     match tokens_to_value(token) {
                         Some(Value::Func(func)) => statements.push(Statement::Function(*func)),
 
-                        val => panic!("{:?}", val),
+                        _ => return Err(Some(start)),
                     }
 
                     index += 1;
                 } else {
-                    panic!()
+                    return Err(Some(start));
                 }
             }
 
             Token::Match => {
                 let val_index = index + 1;
 
-                while tokens[index] != Token::OpenBrace {
-                    index += 1;
-                }
+                index = find(index, T::OpenBrace)?;
 
-                let val = tokens_to_value(&tokens[val_index..index]).unwrap();
+                let val = value(&tokens[val_index..index])?;
 
                 let mut clauses = Vec::new();
 
@@ -518,24 +629,29 @@ pub fn tokens_to_statements(tokens: &[Token]) -> Result<Vec<Statement>, Option<u
 
                     let clause_index = index;
 
-                    index = find_next_balanced(tokens, index);
+                    index = balanced(index)?;
 
                     if clause_val != "_" {
                         clauses.push((
-                            str_to_type(clause_val).unwrap(),
-                            tokens_to_statements(&tokens[clause_index + 1..index]).unwrap(),
+                            str_to_type(clause_val).ok_or(Some(start))?,
+                            sub_block(clause_index, index)?,
                         ));
-                    } else {
-                        assert_eq!(&tokens[clause_index + 1..index], []); // todo add ablity to have default branch
+                    } else if clause_index + 1 != index {
+                        // todo add ablity to have default branch
+                        return Err(Some(start));
                     }
 
                     index += 1;
 
-                    if tokens[index] == Token::Comma {
+                    if let Some(Token::Comma) = tokens.get(index) {
                         index += 1;
                     }
                 }
 
+                if tokens.get(index) != Some(&T::CloseBrace) {
+                    return Err(Some(start));
+                }
+
                 statements.push(Statement::Match(val, clauses));
 
                 index += 1;
@@ -546,41 +662,44 @@ pub fn tokens_to_statements(tokens: &[Token]) -> Result<Vec<Statement>, Option<u
 
                 let val_index = index;
 
-                while tokens[index] != Token::OpenBrace {
-                    index += 1;
-                }
-
-                let code_index = index;
+                let code_index = find(index, T::OpenBrace)?;
 
-                index = find_next_balanced(tokens, index);
+                index = balanced(code_index)?;
 
                 statements.push(Statement::If(
-                    tokens_to_value(&tokens[val_index..code_index]).unwrap(),
-                    tokens_to_statements(&tokens[code_index + 1..index]).unwrap(),
+                    value(&tokens[val_index..code_index])?,
+                    sub_block(code_index, index)?,
                 ));
 
                 index += 1;
             }
 
-            token => {
-                panic!("{:?} {:?}", token, index)
-            }
+            _ => return Err(Some(start)),
         };
+
+        block.append(&mut with_span(span, statements));
     }
 }
 
+// the span goes to the first of the statements
+fn with_span<T>(span: Option<Span>, statements: Vec<T>) -> Vec<(Option<Span>, T)> {
+    statements
+        .into_iter()
+        .enumerate()
+        .map(|(index, statement)| (span.filter(|_| index == 0), statement))
+        .collect()
+}
+
 // set index at 1st instance of the value
-// the result will be the inverse of the token
-fn find_next_balanced(tokens: &[Token], mut index: usize) -> usize {
-    let target = &tokens[index];
+// the result will be the inverse of the token or None if it isn't closed
+fn find_next_balanced(tokens: &[Token], mut index: usize) -> Option<usize> {
+    let target = tokens.get(index)?;
 
-    let inv_target = match tokens[index] {
+    let inv_target = match target {
         Token::OpenBrace => Token::CloseBrace,
         Token::OpenParens => Token::CloseParens,
         Token::OpenBracket => Token::CloseBracket,
-        _ => {
-            unimplemented!()
-        }
+        _ => return None,
     };
 
     let mut depth = 1;
@@ -588,9 +707,11 @@ fn find_next_balanced(tokens: &[Token], mut index: usize) -> usize {
     index += 1;
 
     loop {
-        if tokens[index] == *target {
+        let token = tokens.get(index)?;
+
+        if token == target {
             depth += 1;
-        } else if tokens[index] == inv_target {
+        } else if *token == inv_target {
             depth -= 1;
         }
 
@@ -601,15 +722,38 @@ fn find_next_balanced(tokens: &[Token], mut index: usize) -> usize {
         index += 1;
     }
 
-    index
+    Some(index)
 }
 
-pub fn tokens_to_value(tokens: &[Token]) -> Option<Value> {
+// the index of the first of the token at or after index
+fn find_token(tokens: &[Token], index: usize, token: &Token) -> Option<usize> {
+    tokens
+        .get(index..)?
+        .iter()
+        .position(|other| other == token)
+        .map(|position| position + index)
+}
+
+// the value passed to a function, which is Some(None) if nothing was passed
+fn args_to_value(tokens: &[Token]) -> Option<Option<Value>> {
+    if tokens.is_empty() {
+        Some(None)
+    } else {
+        tokens_to_value(tokens).map(Some)
+    }
+}
 
-    // dbg!(tokens);
+// None if the tokens aren't a value
+pub(crate) fn tokens_to_value(tokens: &[Token]) -> Option<Value> {
+    use Token as T;
 
     let mut index = 0;
 
+    // references are the same as the value
+    if let Some(Token::Ampersand) = tokens.first() {
+        return tokens_to_value(&tokens[1..]);
+    }
+
     let Some(Token::Name(ref str)) = tokens.first() else {
         return None;
     };
@@ -619,75 +763,83 @@ pub fn tokens_to_value(tokens: &[Token]) -> Option<Value> {
             return Some(str_to_value(str));
         }
         Some(Token::OpenBracket) => {
-            while *tokens.get(index).unwrap() != Token::CloseBracket {
-                index += 1;
-            }
-            Value::Func(Box::from(Function::Index(
-                str.clone(),
-                tokens_to_value(&tokens[2..index]).unwrap(), // if [] will panic
-            )))
+            index = find_token(tokens, index, &Token::CloseBracket)?;
+
+            Value::Func(Box::from(Function::Index(str.clone(), tokens_to_value(&tokens[2..index])?)))
         }
         Some(Token::OpenParens) => {
+            index = find_next_balanced(tokens, 1)?;
 
-            // while *tokens.get(index).unwrap() != Token::CloseParens {
-            //     index += 1;
-            // }
+            Value::Func(Box::from(Function::parens_call(str, args_to_value(&tokens[2..index])?)?))
+        }
+        Some(Token::Colon) => {
+            let Some([Token::Colon, Token::Name(ref func_name), Token::OpenParens]) = tokens.get(2..5) else {
+                return None;
+            };
 
-            index = find_next_balanced(tokens, 1);
+            index = find_next_balanced(tokens, 4)?;
 
             Value::Func(Box::from(Function::parens_call(
-                str,
-                tokens_to_value(&tokens[2..index]),
-            )))
+                &format!("{str}::{func_name}"),
+                args_to_value(&tokens[5..index])?,
+            )?))
         }
         Some(Token::Dot) => {
-            if let [Token::Name(ref func_name), Token::OpenParens] = &tokens[2..4] {
+            let Some([Token::Name(ref func_name), Token::OpenParens]) = tokens.get(2..4) else {
+                return None;
+            };
 
-                index = find_next_balanced(tokens, 3);
+            index = find_next_balanced(tokens, 3)?;
 
-                // Value::Func(Box::from(Function::dot_call(
-                //     func_name,
-                //     str,
-                //     tokens_to_value(&tokens[4..index]),
-                // )))
+            // only the last argument is a value, any before it are checked by the function
+            let value_index = tokens[4..index]
+                .iter()
+                .rposition(|token| *token == Token::Comma)
+                .map_or(4, |comma_index| comma_index + 5);
 
-                let value = tokens_to_value(&tokens[4..index]);
+            let value = args_to_value(&tokens[value_index..index])?;
 
-                Value::Func(Box::new(match func_name.as_str() {
-                    "len" => {
-                        assert_eq!(value, None);
-                        Function::Len(String::from(str))
-                    }
-                    "push" => {
-                        if let Some(val) = value {
-                            Function::Push(String::from(str), val)
-                        } else {
-                            panic!()
-                        }
+            Value::Func(Box::new(match (func_name.as_str(), value) {
+                ("len", None) => Function::Len(String::from(str)),
+                ("push", Some(val)) => Function::Push(String::from(str), val),
+                // only inserting at the front is supported
+                ("insert", Some(val)) if tokens[4..value_index] == [T::Name(String::from("0")), T::Comma] => {
+                    Function::PushFront(String::from(str), val)
+                }
+                ("starts_with", Some(val)) => Function::StartsWith(String::from(str), val),
+                ("contains", Some(val)) => Function::Contains(String::from(str), val),
+                ("find", Some(val)) => {
+                    let Some([T::Dot, T::Name(unwrap), T::OpenParens, T::CloseParens]) =
+                        tokens.get(index + 1..index + 5)
+                    else {
+                        return None;
+                    };
+
+                    if unwrap != "unwrap" {
+                        return None;
                     }
-                    "chars" => {
-                        use Token as T;
-                        if let [T::Dot, T::Name(nth), T::OpenParens, T::Name(val), T::CloseParens, T::Dot, T::Name(unwrap), T::OpenParens, T::CloseParens] =
-                            &tokens[index + 1..index + 10]
-                        {
-                            assert_eq!(value, None);
-                            assert_eq!(nth, "nth");
-                            assert_eq!(unwrap, "unwrap");
 
-                            index += 9;
+                    index += 4;
 
-                            Function::IndexStr(String::from(str), str_to_value(val))
-                        } else {
-                            panic!()
-                        }
-                    }
-                    _ => {
-                        panic!("Unknown function name: {}", func_name)
+                    Function::Find(String::from(str), val)
+                }
+                ("chars", None) => {
+                    let Some([T::Dot, T::Name(nth), T::OpenParens, T::Name(val), T::CloseParens, T::Dot, T::Name(unwrap), T::OpenParens, T::CloseParens]) =
+                        tokens.get(index + 1..index + 10)
+                    else {
+                        return None;
+                    };
+
+                    if nth != "nth" || unwrap != "unwrap" {
+                        return None;
                     }
-                }))
-            } else {
-                panic!()
-            }
+
+                    index += 9;
+
+                    Function::IndexStr(String::from(str), str_to_value(val))
+                }
+                _ => return None,
+            }))
         }
         Some(_) => str_to_value(str),
     };
@@ -701,22 +853,21 @@ pub fn tokens_to_value(tokens: &[Token]) -> Option<Value> {
     Some(match operand {
         Token::GreaterThan => Value::Func(Box::from(Function::GreaterThan(
             val,
-            tokens_to_value(&tokens[index + 1..]).unwrap(),
+            tokens_to_value(&tokens[index + 1..])?,
         ))),
         Token::LessThan => Value::Func(Box::from(Function::LessThan(
             val,
-            tokens_to_value(&tokens[index + 1..]).unwrap(),
+            tokens_to_value(&tokens[index + 1..])?,
         ))),
         Token::Equal => Value::Func(Box::from(Function::Equal(
             val,
-            tokens_to_value(&tokens[index + 2..]).unwrap(),
+            tokens_to_value(tokens.get(index + 2..)?)?,
         ))),
 
-        val => {
-            panic!("{:?}", val);
-        } // oper @ ([T::GreaterThan, T::Equal] | [T::LessThan, T::Equal] | [T::Equal, T::Equal] | [T::]) => {
-          //     Value::Func(Token::into_str(oper), vec![val, tokens_to_value(&tokens[index+2..])?])
-          // }
+        // oper @ ([T::GreaterThan, T::Equal] | [T::LessThan, T::Equal] | [T::Equal, T::Equal] | [T::]) => {
+        //     Value::Func(Token::into_str(oper), vec![val, tokens_to_value(&tokens[index+2..])?])
+        // }
+        _ => return None,
     })
 }
 
@@ -730,6 +881,12 @@ fn str_to_value(str: &str) -> Value {
 }
 
 fn str_to_type(value: &str) -> Option<Type> {
+    match value {
+        "true" => return Some(Type::Bool(true)),
+        "false" => return Some(Type::Bool(false)),
+        _ => {}
+    }
+
     match value.chars().next()? {
         '\'' => {
             if value.chars().count() != 3 {
@@ -738,106 +895,347 @@ fn str_to_type(value: &str) -> Option<Type> {
 
             Some(Type::Char(value.chars().nth(1)? as u8))
         }
+        '"' => Some(Type::from(&value[1..value.len() - 1])),
         _ => Some(Type::U32(value.parse().ok()?)),
     }
 }
 
+// push_str is expanded to a push for each char of a static string or to a while loop over a variable
+// None if the value isn't a string
+fn push_str_statements(var: &str, value: Value) -> Option<Vec<Statement>> {
+    Some(match value {
+        Value::Static(Type::FString(str)) => str
+            .into_iter()
+            .map(|char| {
+                Statement::Function(Function::Push(
+                    String::from(var),
+                    Value::Static(Type::Char(char)),
+                ))
+            })
+            .collect(),
+        Value::Func(func) => {
+            let Function::CloneU32(str_var) = *func else {
+                return None;
+            };
+
+            // names can't contain a dot so this won't clash with a variable
+            let counter = format!("{var}.push_str");
+            let clone_counter = || Value::Func(Box::new(Function::CloneU32(counter.clone())));
+
+            vec![
+                Statement::Function(Function::Assign(
+                    counter.clone(),
+                    Value::Static(Type::U32(0)),
+                )),
+                Statement::While(
+                    Value::Func(Box::new(Function::LessThan(
+                        clone_counter(),
+                        Value::Func(Box::new(Function::Len(str_var.clone()))),
+                    ))),
+                    with_span(
+                        None,
+                        vec![
+                            Statement::Function(Function::Push(
+                                String::from(var),
+                                Value::Func(Box::new(Function::IndexStr(str_var, clone_counter()))),
+                            )),
+                            Statement::Function(Function::Assign(
+                                counter.clone(),
+                                Value::Func(Box::new(Function::Add(
+                                    clone_counter(),
+                                    Value::Static(Type::U32(1)),
+                                ))),
+                            )),
+                        ],
+                    ),
+                ),
+            ]
+        }
+        _ => return None,
+    })
+}
+
 // lables each variable with the amount of space it needs
-pub fn annotate_statements(statements: &[Statement], scope: &mut Vec<Vec<Variable>>) -> AnnotatedBlock {
+pub(crate) fn annotate_statements(
+    statements: &[(Option<Span>, Statement)],
+    scope: &mut Vec<Vec<Variable>>,
+) -> Result<AnnotatedBlock, CompileError> {
     scope.push(Vec::new());
 
-    let anno_states = statements
-        .iter()
-        .map(|statement| {
-            match statement {
-                Statement::If(val, code) => {
-                    // annotate_value(val, &mut current_scope);
+    let anno_states = annotate_block(statements, scope);
 
-                    let statement2 = annotate_statements(code, scope);
+    let vars = scope.pop().unwrap();
 
-                    AnnotatedStatement::If(val.clone(), statement2)
+    Ok((anno_states?, vars))
+}
+
+fn annotate_block(
+    statements: &[(Option<Span>, Statement)],
+    scope: &mut Vec<Vec<Variable>>,
+) -> Result<Vec<(Option<Span>, AnnotatedStatement)>, CompileError> {
+    let mut anno_states = Vec::new();
+
+    for (span, statement) in statements {
+        // x += y on a string is the same as x.push_str(y)
+        if let Statement::Function(Function::Assign(var, Value::Func(func))) = statement {
+            if let Function::Add(Value::Func(clone), val) = &**func {
+                if **clone == Function::CloneU32(var.clone()) && is_str_var(scope, var) {
+                    let statements = push_str_statements(var, val.clone())
+                        .ok_or_else(|| CompileError::Type(format!("{:?} can't be pushed to {}", val, var)))?;
+
+                    anno_states.append(&mut annotate_statements_in(&with_span(*span, statements), scope)?);
+
+                    continue;
                 }
-                Statement::Match(val, match_arms) => {
-                    annotate_value(val, scope);
+            }
+        }
+
+        let anno_state = match statement {
+            Statement::If(val, code) => {
+                annotate_condition(val, EmptyType::Bool, scope)?;
 
-                    // let scope = Some(&mut current_scope);
+                let statement2 = annotate_statements(code, scope)?;
 
-                    // let anno_arms = match_arms.iter().map(
-                    //     move |(bftype, statements)|(bftype.clone(), annotate_statements(statements, scope))
-                    // ).collect();
+                AnnotatedStatement::If(val.clone(), statement2)
+            }
+            Statement::Match(val, match_arms) => {
+                annotate_condition(val, EmptyType::Char, scope)?;
+
+                // let scope = Some(&mut current_scope);
 
-                    let mut anno_arms = Vec::new();
+                // let anno_arms = match_arms.iter().map(
+                //     move |(bftype, statements)|(bftype.clone(), annotate_statements(statements, scope))
+                // ).collect();
 
-                    for (bftype, statements) in match_arms {
-                        anno_arms.push((bftype.clone(), annotate_statements(statements, scope)));
+                let mut anno_arms = Vec::new();
+
+                for (bftype, statements) in match_arms {
+                    if !matches!(bftype, Type::Char(_)) {
+                        return Err(CompileError::Type(format!("the match arm {:?} isn't a char", bftype)));
                     }
 
-                    AnnotatedStatement::Match(val.clone(), anno_arms)
-                }
-                Statement::While(val, code) => {
-                    annotate_value(val, scope);
-                    AnnotatedStatement::While(val.clone(), annotate_statements(code, scope))
+                    anno_arms.push((bftype.clone(), annotate_statements(statements, scope)?));
                 }
-                Statement::Function(func) => {
-                    annotate_func(func, scope);
-                    AnnotatedStatement::Function(func.clone())
+
+                AnnotatedStatement::Match(val.clone(), anno_arms)
+            }
+            Statement::While(val, code) => {
+                annotate_condition(val, EmptyType::Bool, scope)?;
+                AnnotatedStatement::While(val.clone(), annotate_statements(code, scope)?)
+            }
+            Statement::Function(func) => {
+                if annotate_func(func, scope)?.is_some() {
+                    return Err(CompileError::Type(format!("the value of {:?} isn't used", func)));
                 }
+
+                AnnotatedStatement::Function(func.clone())
             }
-        })
-        .collect::<Vec<AnnotatedStatement>>();
+        };
+
+        anno_states.push((*span, anno_state));
+    }
+
+    Ok(anno_states)
+}
+
+// annotates statements that are part of the current block
+fn annotate_statements_in(
+    statements: &[(Option<Span>, Statement)],
+    scope: &mut Vec<Vec<Variable>>,
+) -> Result<Vec<(Option<Span>, AnnotatedStatement)>, CompileError> {
+    let (anno_states, mut vars) = annotate_statements(statements, scope)?;
+
+    scope.last_mut().unwrap().append(&mut vars);
 
-    (anno_states, scope.pop().unwrap())
+    Ok(anno_states)
 }
 
-fn annotate_value(value: &Value, scope: &mut [Vec<Variable>]) {
+// the value an if, while or match is on
+fn annotate_condition(value: &Value, bf_type: EmptyType, scope: &mut [Vec<Variable>]) -> Result<(), CompileError> {
+    let found = annotate_value(value, scope)?;
+
+    expect_type(value, found, &[bf_type]).map(|_| ())
+}
+
+// returns the type of the value
+fn annotate_value(value: &Value, scope: &mut [Vec<Variable>]) -> Result<EmptyType, CompileError> {
     match value {
-        Value::Func(func) => annotate_func(func, scope),
-        Value::Static(_) => {}
+        Value::Func(func) => annotate_func(func, scope)?
+            .ok_or_else(|| CompileError::Type(format!("{:?} doesn't return a value", func))),
+        Value::Static(val) => Ok(EmptyType::from(val)),
+    }
+}
+
+// annotates the value and checks it is one of the types
+fn annotate_typed(value: &Value, types: &[EmptyType], scope: &mut [Vec<Variable>]) -> Result<EmptyType, CompileError> {
+    let found = annotate_value(value, scope)?;
+
+    expect_type(value, found, types)
+}
+
+fn expect_type(value: &impl fmt::Debug, found: EmptyType, types: &[EmptyType]) -> Result<EmptyType, CompileError> {
+    if types.contains(&found) {
+        Ok(found)
+    } else {
+        Err(CompileError::Type(format!("{:?} is a {:?} not a {:?}", value, found, types)))
     }
 }
 
-fn annotate_func(func: &Function, scope: &mut [Vec<Variable>]) {
+// the variable has to be declared in this block or one around it
+// returns the type of the variable if it is one of the types
+fn use_var(
+    scope: &mut [Vec<Variable>],
+    var_name: &str,
+    types: &[EmptyType],
+    min_val: usize,
+) -> Result<EmptyType, CompileError> {
+    let found = var_type(scope, var_name).ok_or_else(|| CompileError::Type(format!("{} isn't declared", var_name)))?;
+
+    increase_req_space(scope, var_name, min_val).unwrap();
+
+    expect_type(&var_name, found, types)
+}
+
+fn var_type(scope: &[Vec<Variable>], var_name: &str) -> Option<EmptyType> {
+    scope
+        .iter()
+        .rev()
+        .flatten()
+        .find(|(name, _, _)| name == var_name)
+        .map(|(_, bf_type, _)| bf_type.clone())
+}
+
+const STRINGS: &[EmptyType] = &[EmptyType::FString, EmptyType::IString];
+
+// checks the types of the function's values and returns the type the function returns
+fn annotate_func(func: &Function, scope: &mut [Vec<Variable>]) -> Result<Option<EmptyType>, CompileError> {
+    use EmptyType::{Array, Char, FString, IString, U32};
+
     match func {
         Function::Assign(var, val) => {
-            annotate_value(val, scope);
-            match increase_req_space(scope, var, 0) {
-                None => scope
-                    .last_mut()
-                    .unwrap()
-                    .push((var.clone(), val.bftype(), 0)),
-                Some(_) => {}
+            let val_type = annotate_value(val, scope)?;
+
+            match var_type(scope, var) {
+                Some(var_type) => {
+                    use_var(scope, var, &[val_type], 0)
+                        .map_err(|_| CompileError::Type(format!("{} is a {:?} not a {:?}", var, var_type, val)))?;
+                }
+                None => scope.last_mut().unwrap().push((var.clone(), val_type, 0)),
             }
         }
 
-        Function::Len(var) | Function::CloneU32(var) => {
-            increase_req_space(scope, var, 2).unwrap();
+        Function::Len(var) => {
+            use_var(scope, var, &[FString, IString, Array], 2)?;
+        }
+
+        Function::CloneU32(var) => {
+            use_var(scope, var, &[U32], 2)?;
         }
 
         Function::PrintU32(val) => {
-            annotate_value(val, scope);
+            annotate_typed(val, &[U32, Char], scope)?;
         }
 
-        Function::Index(var, val) | Function::Push(var, val) | Function::IndexStr(var, val) => {
-            increase_req_space(scope, var, 2).unwrap();
-            annotate_value(val, scope);
+        Function::Index(var, val) => {
+            use_var(scope, var, &[Array], 2)?;
+            annotate_typed(val, &[U32], scope)?;
         }
 
-        Function::Add(val1, val2)
-        | Function::Subtract(val1, val2)
-        | Function::Equal(val1, val2)
-        | Function::GreaterThan(val1, val2)
-        | Function::LessThan(val1, val2) => {
-            annotate_value(val1, scope);
-            annotate_value(val2, scope);
+        Function::IndexStr(var, val) => {
+            use_var(scope, var, STRINGS, 2)?;
+            annotate_typed(val, &[U32], scope)?;
+        }
+
+        // strings are pushed chars and arrays are pushed numbers
+        Function::Push(var, val) => {
+            let item = match use_var(scope, var, &[FString, IString, Array], 2)? {
+                Array => U32,
+                _ => Char,
+            };
+
+            annotate_typed(val, &[item], scope)?;
+        }
+
+        Function::PushFront(var, val) => {
+            use_var(scope, var, STRINGS, 2)?;
+            annotate_typed(val, &[Char], scope)?;
+        }
+
+        Function::Equal(val1, val2) if is_str_value(val1, scope) || is_str_value(val2, scope) => {
+            annotate_str_value(val1, scope)?;
+            annotate_str_value(val2, scope)?;
+        }
+
+        Function::StartsWith(var, val) => {
+            use_var(scope, var, STRINGS, 8)?;
+            annotate_str_value(val, scope)?;
+        }
+
+        Function::Contains(var, val) | Function::Find(var, val) => {
+            use_var(scope, var, STRINGS, 9)?;
+            annotate_typed(val, &[Char], scope)?;
+        }
+
+        Function::Add(val1, val2) | Function::Subtract(val1, val2) => {
+            annotate_typed(val1, &[U32], scope)?;
+            annotate_typed(val2, &[U32], scope)?;
+        }
+
+        // chars are compared as numbers
+        Function::Equal(val1, val2) | Function::GreaterThan(val1, val2) | Function::LessThan(val1, val2) => {
+            let val_type = annotate_typed(val1, &[U32, Char], scope)?;
+            annotate_typed(val2, &[val_type], scope)?;
         }
 
         Function::IndexSet(var, val1, val2) => {
-            annotate_value(val1, scope);
-            annotate_value(val2, scope);
-            increase_req_space(scope, var, 2).unwrap();
+            annotate_typed(val1, &[U32], scope)?;
+            annotate_typed(val2, &[U32], scope)?;
+            use_var(scope, var, &[Array], 2)?;
+        }
+        Function::InputStr
+        | Function::ReadLine
+        | Function::ReadToEnd
+        | Function::NewArray
+        | Function::NewString
+        | Function::InputU32 => {}
+    }
+
+    Ok(func.return_type())
+}
+
+// a string variable, they are passed to functions as clones
+fn is_str_value(value: &Value, scope: &[Vec<Variable>]) -> bool {
+    match value {
+        Value::Func(func) => matches!(&**func, Function::CloneU32(var) if is_str_var(scope, var)),
+        Value::Static(_) => false,
+    }
+}
+
+// strings are compared with a string literal or another string variable
+// comparing strings needs space after the later string
+fn annotate_str_value(value: &Value, scope: &mut [Vec<Variable>]) -> Result<(), CompileError> {
+    match value {
+        Value::Static(Type::FString(_) | Type::IString(_)) => return Ok(()),
+        Value::Func(func) => {
+            if let Function::CloneU32(var) = &**func {
+                if is_str_var(scope, var) {
+                    return use_var(scope, var, STRINGS, 8).map(|_| ());
+                }
+            }
         }
-        Function::InputStr | Function::NewArray | Function::InputU32 => {}
+        Value::Static(_) => {}
     }
+
+    Err(CompileError::Type(format!("{:?} isn't a string", value)))
+}
+
+fn is_str_var(scope: &[Vec<Variable>], var: &str) -> bool {
+    scope
+        .iter()
+        .flatten()
+        .any(|(name, bf_type, _)| {
+            name == var && matches!(bf_type, EmptyType::FString | EmptyType::IString)
+        })
 }
 
 // returns Some if the value was updated or false if the value wasn't found
@@ -866,26 +1264,518 @@ fn increase_req_space(scope: &mut [Vec<Variable>], var_name: &str, min_val: usiz
     }
 }
 
-pub fn annostatements_to_bfasm(
-    bf_array: &mut Vec<(Option<String>, EmptyType)>,
-    anno_states: &AnnotatedBlock,
-    input: &mut Chars,
-) -> Vec<BfasmOps> {
-    let mut bfasm_ops: Vec<_> = anno_states
-        .0
-        .iter()
-        .flat_map(|statement| -> Vec<BfasmOps> {
-            let code = match statement {
-                AnnotatedStatement::If(val, code) => {
-                    assert_eq!(val.bftype(), EmptyType::Bool);
+// uses inside of a loop count this many times more
+const LOOP_WEIGHT: usize = 10;
+
+// reorders declarations to cut down on pointer travel
+// scratch values are always made at the end of the tape so every use of a variable travels
+// over everything after it, and growing a string or array moves everything after it
+// so fixed size variables and empty strings and arrays are moved down to their first use with
+// the ones used the most going last
+// strings read from input have to be read into the end of the tape so they aren't moved, and
+// the scratch values aren't moved from the end
+pub(crate) fn layout_block(block: &mut AnnotatedBlock) {
+    for (_, statement) in &mut block.0 {
+        match statement {
+            AnnotatedStatement::If(_, code) | AnnotatedStatement::While(_, code) => {
+                layout_block(code)
+            }
+            AnnotatedStatement::Match(_, match_arms) => {
+                match_arms.iter_mut().for_each(|(_, code)| layout_block(code))
+            }
+            AnnotatedStatement::Function(_) => {}
+        }
+    }
+
+    let mut uses = Vec::new();
+    count_block_uses(&block.0, 1, &mut uses);
+
+    let weight = |var: &str| {
+        uses.iter()
+            .find(|(name, _)| name == var)
+            .map_or(0, |(_, weight)| *weight)
+    };
+
+    let mut statements = Vec::new();
+    // declarations waiting to be placed before their first use
+    let mut sunk: Vec<(String, (Option<Span>, AnnotatedStatement))> = Vec::new();
+    let mut seen: Vec<String> = Vec::new();
+
+    for (span, statement) in mem::take(&mut block.0) {
+        let mut vars = Vec::new();
+        count_statement_uses(&statement, 1, &mut vars);
+
+        if let AnnotatedStatement::Function(Function::Assign(var, val)) = &statement {
+            // only the first assignment to a variable in this block declares it
+            let declared = !seen.contains(var)
+                && block.1.iter().any(|(name, _, _)| name == var);
+
+            // setting a static value or making an empty string or array can't have side effects
+            // so it can be moved
+            let movable = match val {
+                Value::Static(bf_type) => matches!(bf_type, Type::U32(_) | Type::I32(_) | Type::Bool(_) | Type::Char(_)),
+                Value::Func(func) => matches!(**func, Function::NewString | Function::NewArray),
+            };
+
+            if declared && movable {
+                seen.push(var.clone());
+
+                sunk.push((var.clone(), (span, statement)));
+                continue;
+            }
+        }
+
+        let mut placed: Vec<_> = sunk
+            .iter()
+            .filter(|(var, _)| vars.iter().any(|(name, _)| name == var))
+            .map(|(var, _)| var.clone())
+            .collect();
+
+        placed.sort_by_key(|var| weight(var));
+
+        for var in placed {
+            let index = sunk.iter().position(|(name, _)| *name == var).unwrap();
+
+            statements.push(sunk.remove(index).1);
+        }
+
+        vars.into_iter().for_each(|(var, _)| {
+            if !seen.contains(&var) {
+                seen.push(var)
+            }
+        });
+
+        statements.push((span, statement));
+    }
+
+    // unused declarations stay at the end
+    statements.extend(sunk.into_iter().map(|(_, statement)| statement));
+
+    block.0 = statements;
+}
+
+fn count_block_uses(
+    block: &[(Option<Span>, AnnotatedStatement)],
+    weight: usize,
+    uses: &mut Vec<(String, usize)>,
+) {
+    block
+        .iter()
+        .for_each(|(_, statement)| count_statement_uses(statement, weight, uses));
+}
+
+fn count_statement_uses(statement: &AnnotatedStatement, weight: usize, uses: &mut Vec<(String, usize)>) {
+    match statement {
+        AnnotatedStatement::If(val, (code, _)) => {
+            count_value_uses(val, weight, uses);
+            count_block_uses(code, weight, uses);
+        }
+        AnnotatedStatement::Match(val, match_arms) => {
+            count_value_uses(val, weight, uses);
+            match_arms
+                .iter()
+                .for_each(|(_, (code, _))| count_block_uses(code, weight, uses));
+        }
+        AnnotatedStatement::While(val, (code, _)) => {
+            count_value_uses(val, weight * LOOP_WEIGHT, uses);
+            count_block_uses(code, weight * LOOP_WEIGHT, uses);
+        }
+        AnnotatedStatement::Function(func) => count_func_uses(func, weight, uses),
+    }
+}
+
+fn count_value_uses(value: &Value, weight: usize, uses: &mut Vec<(String, usize)>) {
+    if let Value::Func(func) = value {
+        count_func_uses(func, weight, uses)
+    }
+}
+
+fn count_func_uses(func: &Function, weight: usize, uses: &mut Vec<(String, usize)>) {
+    let mut add_use = |var: &String| match uses.iter_mut().find(|(name, _)| name == var) {
+        Some((_, count)) => *count += weight,
+        None => uses.push((var.clone(), weight)),
+    };
+
+    match func {
+        Function::IndexStr(var, val)
+        | Function::Index(var, val)
+        | Function::Assign(var, val)
+        | Function::Push(var, val)
+        | Function::PushFront(var, val)
+        | Function::StartsWith(var, val)
+        | Function::Contains(var, val)
+        | Function::Find(var, val) => {
+            add_use(var);
+            count_value_uses(val, weight, uses);
+        }
+        Function::IndexSet(var, val1, val2) => {
+            add_use(var);
+            count_value_uses(val1, weight, uses);
+            count_value_uses(val2, weight, uses);
+        }
+        Function::Add(val1, val2)
+        | Function::Subtract(val1, val2)
+        | Function::Equal(val1, val2)
+        | Function::GreaterThan(val1, val2)
+        | Function::LessThan(val1, val2) => {
+            count_value_uses(val1, weight, uses);
+            count_value_uses(val2, weight, uses);
+        }
+        Function::Len(var) | Function::CloneU32(var) => add_use(var),
+        Function::PrintU32(val) => count_value_uses(val, weight, uses),
+        Function::InputStr
+        | Function::ReadLine
+        | Function::ReadToEnd
+        | Function::NewString
+        | Function::NewArray
+        | Function::InputU32 => {}
+    }
+}
+
+// evaluates values that only use literals and removes branches that can't be taken
+pub(crate) fn fold_constants(block: &mut AnnotatedBlock) {
+    let mut statements = Vec::new();
+
+    for (span, mut statement) in mem::take(&mut block.0) {
+        match &mut statement {
+            AnnotatedStatement::If(val, code) | AnnotatedStatement::While(val, code) => {
+                fold_value(val);
+                fold_constants(code);
+            }
+            AnnotatedStatement::Match(val, match_arms) => {
+                fold_value(val);
+                match_arms.iter_mut().for_each(|(_, code)| fold_constants(code));
+            }
+            AnnotatedStatement::Function(func) => fold_func(func),
+        }
+
+        // a block that is always run has its statements and variables moved into this block
+        // unless one of its variables has the same name as one in this block
+        match statement {
+            AnnotatedStatement::If(Value::Static(Type::Bool(true)), (mut code, mut vars))
+                if !shares_name(&block.1, &vars) =>
+            {
+                statements.append(&mut code);
+                block.1.append(&mut vars);
+            }
+            AnnotatedStatement::If(Value::Static(Type::Bool(false)), _)
+            | AnnotatedStatement::While(Value::Static(Type::Bool(false)), _) => {}
+            AnnotatedStatement::Match(Value::Static(char @ Type::Char(_)), match_arms)
+                if match_arms
+                    .iter()
+                    .filter(|(arm, _)| *arm == char)
+                    .all(|(_, (_, vars))| !shares_name(&block.1, vars)) =>
+            {
+                if let Some((_, (mut code, mut vars))) =
+                    match_arms.into_iter().find(|(arm, _)| *arm == char)
+                {
+                    statements.append(&mut code);
+                    block.1.append(&mut vars);
+                }
+            }
+            statement => statements.push((span, statement)),
+        }
+    }
+
+    block.0 = statements;
+}
+
+fn shares_name(block_vars: &[Variable], vars: &[Variable]) -> bool {
+    vars.iter().any(|(name, ..)| block_vars.iter().any(|(block_name, ..)| block_name == name))
+}
+
+fn fold_value(value: &mut Value) {
+    let Value::Func(func) = value else {
+        return;
+    };
+
+    fold_func(func);
+
+    let folded = match &**func {
+        Function::Add(Value::Static(Type::U32(val1)), Value::Static(Type::U32(val2))) => {
+            val1.checked_add(*val2).map(Type::U32)
+        }
+        // an underflow is left to be found when the code is run
+        Function::Subtract(Value::Static(Type::U32(val1)), Value::Static(Type::U32(val2))) => {
+            val1.checked_sub(*val2).map(Type::U32)
+        }
+        Function::Equal(Value::Static(val1), Value::Static(val2))
+            if EmptyType::from(val1) == EmptyType::from(val2) =>
+        {
+            Some(Type::Bool(val1 == val2))
+        }
+        Function::GreaterThan(Value::Static(Type::U32(val1)), Value::Static(Type::U32(val2))) => {
+            Some(Type::Bool(val1 > val2))
+        }
+        Function::LessThan(Value::Static(Type::U32(val1)), Value::Static(Type::U32(val2))) => {
+            Some(Type::Bool(val1 < val2))
+        }
+        _ => None,
+    };
+
+    if let Some(bf_type) = folded {
+        *value = Value::Static(bf_type);
+    }
+}
+
+fn fold_func(func: &mut Function) {
+    match func {
+        Function::IndexStr(_, val)
+        | Function::Index(_, val)
+        | Function::Assign(_, val)
+        | Function::Push(_, val)
+        | Function::PushFront(_, val)
+        | Function::StartsWith(_, val)
+        | Function::Contains(_, val)
+        | Function::Find(_, val)
+        | Function::PrintU32(val) => fold_value(val),
+        Function::IndexSet(_, val1, val2)
+        | Function::Add(val1, val2)
+        | Function::Subtract(val1, val2)
+        | Function::Equal(val1, val2)
+        | Function::GreaterThan(val1, val2)
+        | Function::LessThan(val1, val2) => {
+            fold_value(val1);
+            fold_value(val2);
+        }
+        Function::Len(_)
+        | Function::CloneU32(_)
+        | Function::InputStr
+        | Function::ReadLine
+        | Function::ReadToEnd
+        | Function::NewString
+        | Function::NewArray
+        | Function::InputU32 => {}
+    }
+}
+
+// removes assignments to fixed size variables that are never read
+pub(crate) fn remove_dead_assigns(block: &mut AnnotatedBlock) {
+    for (_, statement) in &mut block.0 {
+        match statement {
+            AnnotatedStatement::If(_, code) | AnnotatedStatement::While(_, code) => {
+                remove_dead_assigns(code)
+            }
+            AnnotatedStatement::Match(_, match_arms) => {
+                match_arms.iter_mut().for_each(|(_, code)| remove_dead_assigns(code))
+            }
+            AnnotatedStatement::Function(_) => {}
+        }
+    }
+
+    let dead: Vec<String> = block
+        .1
+        .iter()
+        .filter(|(var, bf_type, _)| {
+            let mut vals = Vec::new();
+            assigned_values(&block.0, var, &mut vals);
+
+            matches!(bf_type, EmptyType::U32 | EmptyType::Bool | EmptyType::Char)
+                && !is_read(&block.0, var)
+                // input has to be read even if it isn't used
+                && vals.into_iter().all(is_pure)
+        })
+        .map(|(var, _, _)| var.clone())
+        .collect();
+
+    for var in &dead {
+        remove_assigns(&mut block.0, var);
+    }
+
+    block.1.retain(|(var, _, _)| !dead.contains(var));
+}
+
+// if the variable is used anywhere other than being assigned to
+fn is_read(block: &[(Option<Span>, AnnotatedStatement)], var: &str) -> bool {
+    block.iter().any(|(_, statement)| {
+        let mut uses = Vec::new();
+
+        match statement {
+            AnnotatedStatement::Function(Function::Assign(_, val)) => {
+                count_value_uses(val, 1, &mut uses)
+            }
+            AnnotatedStatement::If(val, (code, _)) | AnnotatedStatement::While(val, (code, _)) => {
+                count_value_uses(val, 1, &mut uses);
+
+                if is_read(code, var) {
+                    return true;
+                }
+            }
+            AnnotatedStatement::Match(val, match_arms) => {
+                count_value_uses(val, 1, &mut uses);
+
+                if match_arms.iter().any(|(_, (code, _))| is_read(code, var)) {
+                    return true;
+                }
+            }
+            AnnotatedStatement::Function(func) => count_func_uses(func, 1, &mut uses),
+        }
+
+        uses.iter().any(|(name, _)| name == var)
+    })
+}
+
+fn assigned_values<'a>(
+    block: &'a [(Option<Span>, AnnotatedStatement)],
+    var: &str,
+    vals: &mut Vec<&'a Value>,
+) {
+    for (_, statement) in block {
+        match statement {
+            AnnotatedStatement::Function(Function::Assign(name, val)) if name == var => vals.push(val),
+            AnnotatedStatement::If(_, (code, _)) | AnnotatedStatement::While(_, (code, _)) => {
+                assigned_values(code, var, vals)
+            }
+            AnnotatedStatement::Match(_, match_arms) => match_arms
+                .iter()
+                .for_each(|(_, (code, _))| assigned_values(code, var, vals)),
+            AnnotatedStatement::Function(_) => {}
+        }
+    }
+}
+
+fn remove_assigns(block: &mut Vec<(Option<Span>, AnnotatedStatement)>, var: &str) {
+    block.retain(|(_, statement)| {
+        !matches!(statement, AnnotatedStatement::Function(Function::Assign(name, _)) if name == var)
+    });
+
+    for (_, statement) in block {
+        match statement {
+            AnnotatedStatement::If(_, (code, _)) | AnnotatedStatement::While(_, (code, _)) => {
+                remove_assigns(code, var)
+            }
+            AnnotatedStatement::Match(_, match_arms) => match_arms
+                .iter_mut()
+                .for_each(|(_, (code, _))| remove_assigns(code, var)),
+            AnnotatedStatement::Function(_) => {}
+        }
+    }
+}
+
+// if the value can be skipped without changing the program
+fn is_pure(value: &Value) -> bool {
+    let Value::Func(func) = value else {
+        return true;
+    };
+
+    match &**func {
+        Function::InputStr
+        | Function::ReadLine
+        | Function::ReadToEnd
+        | Function::InputU32
+        | Function::PrintU32(_)
+        | Function::Assign(_, _)
+        | Function::IndexSet(_, _, _)
+        | Function::Push(_, _)
+        | Function::PushFront(_, _)
+        // these can fail when they're run so skipping them would skip the error
+        | Function::Subtract(_, _)
+        | Function::Index(_, _)
+        | Function::IndexStr(_, _)
+        | Function::Find(_, _) => false,
+        Function::Add(val1, val2)
+        | Function::Equal(val1, val2)
+        | Function::GreaterThan(val1, val2)
+        | Function::LessThan(val1, val2) => is_pure(val1) && is_pure(val2),
+        Function::StartsWith(_, val) | Function::Contains(_, val) => is_pure(val),
+        Function::Len(_) | Function::CloneU32(_) | Function::NewString | Function::NewArray => true,
+    }
+}
+
+// marks the cells of a variable after its last use
+const FREE_SLOT: &str = "";
+
+// clears a variable after its last use so its cells can be used again
+fn free_var(
+    bf_array: &mut Vec<(Option<String>, EmptyType)>,
+    var_name: &str,
+    spacing: usize,
+    code: &mut Vec<BfasmOps>,
+) {
+    let Some((index, _)) = search_bf(bf_array, var_name) else {
+        return;
+    };
+
+    code.push(BfasmOps::Clear(index));
+
+    bf_array[index..=index + spacing]
+        .iter_mut()
+        .for_each(|cell| *cell = (Some(String::from(FREE_SLOT)), EmptyType::EmptyCell));
+
+    while let Some((Some(name), EmptyType::EmptyCell)) = bf_array.last() {
+        if name != FREE_SLOT {
+            break;
+        }
+
+        bf_array.pop();
+    }
+}
+
+pub(crate) fn annostatements_to_bfasm(
+    bf_array: &mut Vec<(Option<String>, EmptyType)>,
+    anno_states: &AnnotatedBlock,
+    input: &mut Chars,
+) -> Result<Vec<BfasmOps>, CompileError> {
+    let (statement_ops, mut clear_ops) = statements_to_bfasm(bf_array, anno_states, input)?;
+
+    let mut bfasm_ops: Vec<BfasmOps> = statement_ops
+        .into_iter()
+        .flat_map(|(span, code)| match span {
+            Some((line, column)) => vec![BfasmOps::Source(line, column, code)],
+            None => code,
+        })
+        .collect();
+    bfasm_ops.append(&mut clear_ops);
+
+    Ok(bfasm_ops)
+}
 
+// the ops a statement was lowered to and where it starts in the source
+type StatementOps = (Option<Span>, Vec<BfasmOps>);
+
+// returns the ops of each statement and the ops that clear the block's variables
+fn statements_to_bfasm(
+    bf_array: &mut Vec<(Option<String>, EmptyType)>,
+    anno_states: &AnnotatedBlock,
+    input: &mut Chars,
+) -> Result<(Vec<StatementOps>, Vec<BfasmOps>), CompileError> {
+    // freed cells can only be reused by variables of the same block
+    let block_start = bf_array.len();
+
+    // the statement each variable is last used in
+    // only fixed size variables can be cleared from the middle of the tape
+    let last_uses: Vec<(usize, &Variable)> = anno_states
+        .1
+        .iter()
+        .filter(|(_, bf_type, _)| matches!(bf_type, EmptyType::U32 | EmptyType::Bool | EmptyType::Char))
+        .filter_map(|var| {
+            anno_states
+                .0
+                .iter()
+                .rposition(|(_, statement)| {
+                    let mut uses = Vec::new();
+                    count_statement_uses(statement, 1, &mut uses);
+
+                    uses.iter().any(|(name, _)| *name == var.0)
+                })
+                .map(|index| (index, var))
+        })
+        .collect();
+
+    let statement_ops: Vec<StatementOps> = anno_states
+        .0
+        .iter()
+        .enumerate()
+        .map(|(statement_index, (span, statement))| {
+            let mut code = match statement {
+                AnnotatedStatement::If(val, code) => {
                     let target_val = bf_array.len();
 
-                    let mut bf_code = eval_value(val, bf_array, input);
+                    let mut bf_code = eval_value(val, bf_array, input)?;
 
-                    assert_eq!(bf_array.pop().unwrap(), (None, EmptyType::Bool));
+                    pop_value(bf_array, &[EmptyType::Bool])?;
 
-                    let if_code = annostatements_to_bfasm(bf_array, code, input);
+                    let if_code = annostatements_to_bfasm(bf_array, code, input)?;
 
                     // bf_code.push(Box::new(move |bunf| bunf.bool_while(target_val, &if_code)));
                     bf_code.push(BfasmOps::BoolIf(target_val, if_code));
@@ -893,16 +1783,14 @@ pub fn annostatements_to_bfasm(
                     bf_code
                 }
                 AnnotatedStatement::While(val, code) => {
-                    assert_eq!(val.bftype(), EmptyType::Bool);
-
                     let target_val = bf_array.len();
 
-                    let mut val_code = eval_value(val, bf_array, input);
+                    let mut val_code = eval_value(val, bf_array, input)?;
                     let mut bf_code = val_code.clone();
 
-                    let mut while_code = annostatements_to_bfasm(bf_array, code, input);
+                    let mut while_code = annostatements_to_bfasm(bf_array, code, input)?;
 
-                    assert_eq!(bf_array.pop().unwrap(), (None, EmptyType::Bool));
+                    pop_value(bf_array, &[EmptyType::Bool])?;
 
                     // make sure the val is re calculated at the end of every while
 
@@ -919,13 +1807,14 @@ pub fn annostatements_to_bfasm(
                     bf_code
                 }
                 AnnotatedStatement::Match(val, match_arms) => {
-                    assert_eq!(val.bftype(), EmptyType::Char);
-
                     let target_val = bf_array.len();
 
-                    let mut code = eval_value(val, bf_array, input);
+                    let mut code = eval_value(val, bf_array, input)?;
+
+                    pop_value(bf_array, &[EmptyType::Char])?;
 
-                    assert_eq!(bf_array.pop().unwrap(), (None, EmptyType::Char));
+                    // the arms are run on the match's flags so their values go after them
+                    bf_array.extend((0..6).map(|_| (None, EmptyType::EmptyCell)));
 
                     // TODO validate match arms?
                     // How could they have different results if from good rust
@@ -933,11 +1822,15 @@ pub fn annostatements_to_bfasm(
                     let mut bf_match_arms: Vec<_> = match_arms
                         .iter()
                         .map(|(bf_type, anno_states)| {
-                            let Type::Char(char) = bf_type else { panic!() };
+                            let Type::Char(char) = bf_type else {
+                                return Err(CompileError::Type(format!("the match arm {:?} isn't a char", bf_type)));
+                            };
 
-                            (*char, annostatements_to_bfasm(bf_array, anno_states, input))
+                            Ok((*char, annostatements_to_bfasm(bf_array, anno_states, input)?))
                         })
-                        .collect();
+                        .collect::<Result<_, CompileError>>()?;
+
+                    bf_array.truncate(target_val);
 
                     bf_match_arms.sort_by_key(|(val, _)| *val);
 
@@ -954,9 +1847,9 @@ pub fn annostatements_to_bfasm(
                             {
                                 let var_type = var_type.clone();
 
-                                let mut code = eval_value(val, bf_array, input);
+                                let mut code = eval_value(val, bf_array, input)?;
 
-                                assert_eq!(bf_array.pop().unwrap(), (None, var_type));
+                                pop_value(bf_array, &[var_type])?;
 
                                 let val_pos = bf_array.len();
 
@@ -970,46 +1863,64 @@ pub fn annostatements_to_bfasm(
 
                                 code
                             } else {
-                                // will only panic if there is a var in the statements but not in the Variables
+                                // the annotation adds every variable of the block to its Variables
                                 let (str, bf_type, spacing) = anno_states
                                     .1
                                     .iter()
                                     .find(|(str, _, _)| str == var_name)
-                                    .unwrap();
+                                    .ok_or_else(|| CompileError::Type(format!("{} isn't declared", var_name)))?;
 
-                                let code = eval_value(val, bf_array, input);
+                                let mut code = eval_value(val, bf_array, input)?;
 
-                                let len = bf_array.len() - 1;
+                                let len = last_value(bf_array, std::slice::from_ref(bf_type))?;
 
-                                let (None, val_type) = &bf_array[len] else {
-                                    panic!()
-                                };
+                                // use the cells of a variable that isn't used anymore
+                                let free_slot = (block_start..len).find(|&index| {
+                                    bf_array.get(index..=index + spacing).is_some_and(|cells| {
+                                        cells.iter().all(|(name, _)| name.as_deref() == Some(FREE_SLOT))
+                                    })
+                                });
 
-                                assert_eq!(val_type, bf_type);
+                                match free_slot {
+                                    Some(slot)
+                                        if matches!(
+                                            bf_type,
+                                            EmptyType::U32 | EmptyType::Bool | EmptyType::Char
+                                        ) =>
+                                    {
+                                        code.push(BfasmOps::MoveType(len, slot));
 
-                                bf_array[len].0 = Some(str.clone());
+                                        bf_array.pop();
 
-                                (0..*spacing)
-                                    .for_each(|_| bf_array.push((None, EmptyType::EmptyCell)));
+                                        bf_array[slot] = (Some(str.clone()), bf_type.clone());
+
+                                        bf_array[slot + 1..=slot + spacing]
+                                            .iter_mut()
+                                            .for_each(|cell| *cell = (None, EmptyType::EmptyCell));
+                                    }
+                                    _ => {
+                                        bf_array[len].0 = Some(str.clone());
+
+                                        (0..*spacing)
+                                            .for_each(|_| bf_array.push((None, EmptyType::EmptyCell)));
+                                    }
+                                }
 
                                 code
                             }
                         }
                         Function::IndexSet(var_name, array_index, array_val) => {
-                            let (var_index, (_, EmptyType::Array)) =
-                                search_bf(bf_array, var_name).unwrap()
-                            else {
-                                panic!()
-                            };
+                            let var_index = find_var(bf_array, var_name, &[EmptyType::Array])?;
 
                             let index_index = bf_array.len();
 
-                            let mut code = eval_value(array_index, bf_array, input);
+                            let mut code = eval_value(array_index, bf_array, input)?;
 
-                            code.append(&mut eval_value(array_val, bf_array, input));
+                            code.append(&mut flip_array_index(var_index, index_index));
+                            code.append(&mut eval_value(array_val, bf_array, input)?);
 
-                            assert_eq!(bf_array.pop().unwrap(), (None, EmptyType::U32));
-                            assert_eq!(bf_array.pop().unwrap(), (None, EmptyType::U32));
+                            pop_value(bf_array, &[EmptyType::U32])?;
+                            pop_value(bf_array, &[EmptyType::U32])?;
 
                             // code.push(Box::new(move |x| {
                             //     x.move_type(index_index, var_index + 1)?;
@@ -1023,16 +1934,33 @@ pub fn annostatements_to_bfasm(
 
                             code
                         }
+                        Function::Push(var_name, val)
+                            if matches!(
+                                search_bf(bf_array, var_name),
+                                Some((_, (_, EmptyType::FString | EmptyType::IString)))
+                            ) =>
+                        {
+                            let var_index = find_var(bf_array, var_name, STRINGS)?;
+
+                            let mut code = eval_value(val, bf_array, input)?;
+
+                            pop_value(bf_array, &[EmptyType::Char])?;
+
+                            let val_index = bf_array.len();
+
+                            // the string grows to the left so make room in front of it
+                            code.push(BfasmOps::InsertEC(var_index, 2));
+                            code.push(BfasmOps::MoveType(val_index + 2, var_index + 1));
+                            code.push(BfasmOps::StrPush(var_index + 2));
+
+                            code
+                        }
                         Function::Push(var_name, val) => {
-                            let (var_index, (_, EmptyType::Array)) =
-                                search_bf(bf_array, var_name).unwrap()
-                            else {
-                                panic!()
-                            };
+                            let var_index = find_var(bf_array, var_name, &[EmptyType::Array])?;
 
-                            let mut code = eval_value(val, bf_array, input);
+                            let mut code = eval_value(val, bf_array, input)?;
 
-                            assert_eq!(bf_array.pop().unwrap(), (None, EmptyType::U32));
+                            pop_value(bf_array, &[EmptyType::U32])?;
 
                             let val_index = bf_array.len();
 
@@ -1048,10 +1976,25 @@ pub fn annostatements_to_bfasm(
 
                             code
                         } // need to add push back to bfasm
+                        Function::PushFront(var_name, val) => {
+                            let var_index = find_var(bf_array, var_name, STRINGS)?;
+
+                            let mut code = eval_value(val, bf_array, input)?;
+
+                            pop_value(bf_array, &[EmptyType::Char])?;
+
+                            let val_index = bf_array.len();
+
+                            code.push(BfasmOps::MoveType(val_index, var_index + 1));
+                            code.push(BfasmOps::StrPushF(var_index));
+                            code.push(BfasmOps::InsertEC(var_index + 1, 2));
+
+                            code
+                        }
                         Function::PrintU32(val) => {
-                            let mut code = eval_value(val, bf_array, input);
+                            let mut code = eval_value(val, bf_array, input)?;
 
-                            assert_eq!(bf_array.pop().unwrap(), (None, EmptyType::U32));
+                            pop_value(bf_array, &[EmptyType::U32, EmptyType::Char])?;
 
                             let print_target = bf_array.len();
 
@@ -1066,23 +2009,47 @@ pub fn annostatements_to_bfasm(
                             code
                         }
                         func => {
-                            panic!("{:?}", func)
+                            return Err(CompileError::Type(format!("the value of {:?} isn't used", func)));
                         }
                     }
                 }
             };
 
-            code
+            last_uses
+                .iter()
+                .filter(|(index, _)| *index == statement_index)
+                .for_each(|(_, (var_name, _, spacing))| {
+                    free_var(bf_array, var_name, *spacing, &mut code)
+                });
+
+            Ok((*span, code))
         })
-        .collect();
+        .collect::<Result<_, CompileError>>()?;
+
+    let mut bfasm_ops = Vec::new();
 
     // remove block variables
-    let mut index = bf_array.len() - 1;
+    let Some(mut index) = bf_array.len().checked_sub(1) else {
+        return Ok((statement_ops, bfasm_ops));
+    };
 
     loop {
         match bf_array.get(index) {
             None => break,
             Some((None, EmptyType::EmptyCell)) => {
+                if index == 0 {
+                    break;
+                }
+
+                index -= 1;
+            }
+            Some((Some(found_var), EmptyType::EmptyCell)) if found_var == FREE_SLOT => {
+                bf_array.truncate(index);
+
+                if index == 0 {
+                    break;
+                }
+
                 index -= 1;
             }
             Some((Some(found_var), _)) => {
@@ -1118,27 +2085,38 @@ pub fn annostatements_to_bfasm(
         }
     }
 
-    bfasm_ops
+    Ok((statement_ops, bfasm_ops))
 }
 
-fn eval_value(value: &Value, bf_array: &mut Vec<(Option<String>, EmptyType)>, input: &mut Chars) -> Vec<BfasmOps> {
-    match value {
+// the array ops index from the back so the index at val_index is changed to len - 1 - index
+// the cell after it holds the length while it's flipped
+fn flip_array_index(var_index: usize, val_index: usize) -> Vec<BfasmOps> {
+    vec![
+        BfasmOps::Len(var_index),
+        BfasmOps::MoveType(var_index + 1, val_index + 1),
+        BfasmOps::ArrayFlipIndex(val_index),
+    ]
+}
+
+fn eval_value(
+    value: &Value,
+    bf_array: &mut Vec<(Option<String>, EmptyType)>,
+    input: &mut Chars,
+) -> Result<Vec<BfasmOps>, CompileError> {
+    Ok(match value {
         Value::Func(func) => {
             match &**func {
                 func @ (Function::IndexStr(var_name, val) | Function::Index(var_name, val)) => {
-                    assert_eq!(val.bftype(), EmptyType::U32);
-
-                    let mut code = eval_value(val, bf_array, input);
+                    let mut code = eval_value(val, bf_array, input)?;
 
-                    let (
-                        var_index,
-                        (_, EmptyType::Array | EmptyType::FString | EmptyType::IString),
-                    ) = search_bf(bf_array, var_name).unwrap()
-                    else {
-                        panic!()
+                    let var_types = match func {
+                        Function::IndexStr(_, _) => STRINGS,
+                        _ => &[EmptyType::Array],
                     };
 
-                    let val_index = bf_array.len() - 1;
+                    let var_index = find_var(bf_array, var_name, var_types)?;
+
+                    let val_index = last_value(bf_array, &[EmptyType::U32])?;
 
                     match func {
                         Function::IndexStr(_, _) => {
@@ -1160,6 +2138,7 @@ fn eval_value(value: &Value, bf_array: &mut Vec<(Option<String>, EmptyType)>, in
                             //     x.move_type(var_index + 1, val_index)
                             // }));
 
+                            code.append(&mut flip_array_index(var_index, val_index));
                             code.push(BfasmOps::MoveType(val_index, var_index + 1));
                             code.push(BfasmOps::ArrayIndex(var_index));
                             code.push(BfasmOps::MoveType(var_index + 1, val_index));
@@ -1174,17 +2153,13 @@ fn eval_value(value: &Value, bf_array: &mut Vec<(Option<String>, EmptyType)>, in
                     code
                 }
                 func @ (Function::Add(val1, val2) | Function::Subtract(val1, val2)) => {
-                    assert_eq!(val1.bftype(), EmptyType::U32);
-                    assert_eq!(val2.bftype(), EmptyType::U32);
+                    let mut code = eval_value(val1, bf_array, input)?;
 
-                    let mut code = eval_value(val1, bf_array, input);
+                    code.append(&mut eval_value(val2, bf_array, input)?);
 
-                    code.append(&mut eval_value(val2, bf_array, input));
+                    pop_value(bf_array, &[EmptyType::U32])?;
 
-                    let target_index = bf_array.len() - 2;
-
-                    assert_eq!(bf_array.pop().unwrap(), (None, EmptyType::U32));
-                    assert_eq!(&bf_array[target_index], &(None, EmptyType::U32));
+                    let target_index = last_value(bf_array, &[EmptyType::U32])?;
 
                     match func {
                         Function::Add(_, _) => {
@@ -1202,26 +2177,66 @@ fn eval_value(value: &Value, bf_array: &mut Vec<(Option<String>, EmptyType)>, in
 
                     code
                 }
+                Function::Equal(val1, val2)
+                    if str_var(val1, bf_array).is_some() || str_var(val2, bf_array).is_some() =>
+                {
+                    // the order doesn't matter for equality
+                    let (var_name, val) = match str_var(val1, bf_array) {
+                        Some(var_name) => (var_name, val2),
+                        None => {
+                            let var_name = str_var(val2, bf_array)
+                                .ok_or_else(|| CompileError::Type(format!("{:?} isn't a string", val2)))?;
+
+                            (var_name, val1)
+                        }
+                    };
+
+                    eval_str_compare(var_name, val, BfasmOps::StrEquals, bf_array)?
+                }
+                Function::StartsWith(var_name, val) => {
+                    eval_str_compare(var_name, val, BfasmOps::StrStartsWith, bf_array)?
+                }
+                func @ (Function::Contains(var_name, val) | Function::Find(var_name, val)) => {
+                    let mut code = eval_value(val, bf_array, input)?;
+
+                    let var_index = find_var(bf_array, var_name, STRINGS)?;
+
+                    let val_index = last_value(bf_array, &[EmptyType::Char])?;
+
+                    code.push(BfasmOps::MoveType(val_index, var_index + 1));
+
+                    match func {
+                        Function::Contains(_, _) => {
+                            code.push(BfasmOps::StrContains(var_index));
+                            bf_array[val_index] = (None, EmptyType::Bool);
+                        }
+                        Function::Find(_, _) => {
+                            code.push(BfasmOps::StrFind(var_index));
+                            bf_array[val_index] = (None, EmptyType::U32);
+                        }
+                        _ => {
+                            unreachable!()
+                        }
+                    };
+
+                    code.push(BfasmOps::MoveType(var_index + 1, val_index));
+
+                    code
+                }
                 func @ (Function::Equal(val1, val2)
                 | Function::GreaterThan(val1, val2)
                 | Function::LessThan(val1, val2)) => {
-                    assert_eq!(val1.bftype(), EmptyType::U32);
-                    assert_eq!(val2.bftype(), EmptyType::U32);
-
-                    let mut code = eval_value(val1, bf_array, input);
+                    let mut code = eval_value(val1, bf_array, input)?;
+                    char_as_u32(bf_array, &mut code)?;
                     bf_array.push((None, EmptyType::EmptyCell));
-                    code.append(&mut eval_value(val2, bf_array, input));
+                    code.append(&mut eval_value(val2, bf_array, input)?);
+                    char_as_u32(bf_array, &mut code)?;
 
-                    let target_index = bf_array.len() - 3;
+                    pop_value(bf_array, &[EmptyType::U32])?;
+                    pop_value(bf_array, &[EmptyType::EmptyCell])?;
+                    pop_value(bf_array, &[EmptyType::U32])?;
 
-                    assert_eq!(
-                        [
-                            (None, EmptyType::U32),
-                            (None, EmptyType::EmptyCell),
-                            (None, EmptyType::U32)
-                        ],
-                        bf_array[target_index..bf_array.len()]
-                    );
+                    let target_index = bf_array.len();
 
                     match func {
                         Function::GreaterThan(_, _) => {
@@ -1241,22 +2256,13 @@ fn eval_value(value: &Value, bf_array: &mut Vec<(Option<String>, EmptyType)>, in
                         }
                     };
 
-                    bf_array.pop();
-                    bf_array.pop();
-                    bf_array.pop();
-
                     bf_array.push((None, EmptyType::Bool));
 
                     code
                 }
                 Function::Len(var_name) => {
-                    let (
-                        str_index,
-                        (_, EmptyType::IString | EmptyType::FString | EmptyType::Array),
-                    ) = search_bf(bf_array, var_name).unwrap()
-                    else {
-                        panic!()
-                    };
+                    let str_index =
+                        find_var(bf_array, var_name, &[EmptyType::IString, EmptyType::FString, EmptyType::Array])?;
 
                     let target_index = bf_array.len();
 
@@ -1278,40 +2284,72 @@ fn eval_value(value: &Value, bf_array: &mut Vec<(Option<String>, EmptyType)>, in
                     //     x.input(target_index, Type::from(String::new()))
                     // })]
 
-                    let str = dbg!(input.take_while(|char| *char != '\0').collect::<String>());
-                    assert!(!str.is_empty());
+                    let str = input.take_while(|char| *char != '\0').collect::<String>();
+
+                    if str.is_empty() {
+                        return Err(CompileError::MissingInput);
+                    }
 
                     vec![BfasmOps::Input(
                         target_index,
                         Type::IString(str.into_bytes()),
                     )]
                 }
-                Function::NewArray => {
+                Function::ReadLine => {
                     let target_index = bf_array.len();
 
-                    bf_array.push((None, EmptyType::Array));
+                    bf_array.push((None, EmptyType::IString));
 
-                    // vec![Box::new(move |x| {
+                    // the newline is kept so the op knows the line wasn't ended by eof
+                    let mut str = String::new();
+
+                    for char in input.by_ref() {
+                        str.push(char);
+
+                        if char == '\n' {
+                            break;
+                        }
+                    }
+
+                    vec![BfasmOps::InputLine(target_index, str)]
+                }
+                Function::ReadToEnd => {
+                    let target_index = bf_array.len();
+
+                    bf_array.push((None, EmptyType::IString));
+
+                    vec![BfasmOps::InputToEnd(target_index, input.collect())]
+                }
+                Function::NewArray => {
+                    let target_index = bf_array.len();
+
+                    bf_array.push((None, EmptyType::Array));
+
+                    // vec![Box::new(move |x| {
                     //     x.set(target_index, Type::from(Vec::new()))
                     // })]
 
                     vec![BfasmOps::Set(target_index, Type::from(Vec::new()))]
                 }
+                Function::NewString => {
+                    let target_index = bf_array.len();
+
+                    bf_array.push((None, EmptyType::FString));
+
+                    vec![BfasmOps::Set(target_index, Type::from(String::new()))]
+                }
                 Function::InputU32 => {
                     let target_index = bf_array.len();
                     bf_array.push((None, EmptyType::U32));
 
                     // vec![Box::new(move |x| x.input(target_index, Type::from('a')))]
                     vec![
-                        BfasmOps::Input(target_index, Type::from(input.next().unwrap())),
+                        BfasmOps::Input(target_index, Type::from(input.next().ok_or(CompileError::MissingInput)?)),
                         BfasmOps::CharToU32(target_index),
                     ]
                 }
                 Function::CloneU32(var_name) => {
-                    let (target, (_, EmptyType::U32)) = search_bf(bf_array, var_name).unwrap()
-                    else {
-                        panic!()
-                    };
+                    let target = find_var(bf_array, var_name, &[EmptyType::U32])?;
 
                     let goal_index = bf_array.len();
 
@@ -1324,7 +2362,7 @@ fn eval_value(value: &Value, bf_array: &mut Vec<(Option<String>, EmptyType)>, in
                         BfasmOps::MoveType(target + 1, goal_index),
                     ]
                 }
-                ref func => panic!("{:?}", func),
+                ref func => return Err(CompileError::Type(format!("{:?} doesn't return a value", func))),
             }
         }
         Value::Static(val) => {
@@ -1335,9 +2373,107 @@ fn eval_value(value: &Value, bf_array: &mut Vec<(Option<String>, EmptyType)>, in
             // vec![Box::new(move |x| x.set(index, var.clone()))]
             vec![BfasmOps::Set(index, var.clone())]
         }
+    })
+}
+
+// chars are compared as numbers so a char value is changed to a u32
+fn char_as_u32(bf_array: &mut [(Option<String>, EmptyType)], code: &mut Vec<BfasmOps>) -> Result<(), CompileError> {
+    let index = last_value(bf_array, &[EmptyType::U32, EmptyType::Char])?;
+
+    if bf_array[index].1 == EmptyType::Char {
+        code.push(BfasmOps::CharToU32(index));
+
+        bf_array[index].1 = EmptyType::U32;
+    }
+
+    Ok(())
+}
+
+// returns the variable name if the value is a string variable
+fn str_var<'a>(value: &'a Value, bf_array: &mut [(Option<String>, EmptyType)]) -> Option<&'a str> {
+    let Value::Func(func) = value else {
+        return None;
+    };
+
+    let Function::CloneU32(var_name) = &**func else {
+        return None;
+    };
+
+    match search_bf(bf_array, var_name)? {
+        (_, (_, EmptyType::FString | EmptyType::IString)) => Some(var_name),
+        _ => None,
+    }
+}
+
+// compares a string variable to a string literal or another string variable
+fn eval_str_compare(
+    var_name: &str,
+    value: &Value,
+    op: fn(usize, usize) -> BfasmOps,
+    bf_array: &mut Vec<(Option<String>, EmptyType)>,
+) -> Result<Vec<BfasmOps>, CompileError> {
+    let var_index = find_var(bf_array, var_name, STRINGS)?;
+
+    let target_index = bf_array.len();
+
+    let code = match value {
+        Value::Static(str @ (Type::FString(_) | Type::IString(_))) => {
+            // the literal goes after the result so clearing it doesn't move the result
+            vec![
+                BfasmOps::Set(target_index + 1, str.clone()),
+                op(var_index, target_index + 1),
+                BfasmOps::MoveType(target_index + 2, target_index),
+                BfasmOps::Clear(target_index + 1),
+            ]
+        }
+        value => {
+            let other_name = str_var(value, bf_array)
+                .ok_or_else(|| CompileError::Type(format!("{:?} isn't a string", value)))?;
+            let other_index = find_var(bf_array, other_name, STRINGS)?;
+
+            vec![
+                op(var_index, other_index),
+                BfasmOps::MoveType(std::cmp::max(var_index, other_index) + 1, target_index),
+            ]
+        }
+    };
+
+    bf_array.push((None, EmptyType::Bool));
+
+    Ok(code)
+}
+
+// the annotation checks the types so these only fail if a pass changed them
+fn find_var(
+    bf_array: &mut [(Option<String>, EmptyType)],
+    var_name: &str,
+    types: &[EmptyType],
+) -> Result<usize, CompileError> {
+    match search_bf(bf_array, var_name) {
+        Some((index, (_, bf_type))) if types.contains(bf_type) => Ok(index),
+        Some((_, (_, bf_type))) => {
+            Err(CompileError::Type(format!("{} is a {:?} not a {:?}", var_name, bf_type, types)))
+        }
+        None => Err(CompileError::Type(format!("{} isn't declared", var_name))),
+    }
+}
+
+// the index of the last value on the tape
+fn last_value(bf_array: &[(Option<String>, EmptyType)], types: &[EmptyType]) -> Result<usize, CompileError> {
+    match bf_array.last() {
+        Some((None, bf_type)) if types.contains(bf_type) => Ok(bf_array.len() - 1),
+        cell => Err(CompileError::Type(format!("expected a {:?} value but found {:?}", types, cell))),
     }
 }
 
+fn pop_value(bf_array: &mut Vec<(Option<String>, EmptyType)>, types: &[EmptyType]) -> Result<(), CompileError> {
+    last_value(bf_array, types)?;
+
+    bf_array.pop();
+
+    Ok(())
+}
+
 fn search_bf<'a>(
     bf_array: &'a mut [(Option<String>, EmptyType)],
     var_name: &str,
@@ -1351,25 +2487,351 @@ fn search_bf<'a>(
     })
 }
 
-pub fn bunf(program: &str, input: &mut Chars) -> Result<Bfasm, Vec<OpError>> {
-    let tokens = tokenize(program).unwrap();
+#[cfg(test)]
+pub(crate) fn bunf(program: &str, input: &mut Chars) -> Result<Bfasm, Vec<OpError>> {
+    let compiler = Compiler::new(program).input(&input.collect::<String>());
+
+    match compiler.compile_bfasm() {
+        Ok(bfasm) => Ok(bfasm),
+        Err(CompileError::Ops(errs)) => Err(errs),
+        Err(err) => panic!("{}", err),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum CompileError {
+    // the source has a char that isn't part of bunf
+    Tokenize,
+    // the line and column of the statement that failed if it is known
+    Parse(Option<Span>),
+    // a variable that isn't declared or a value of the wrong type
+    Type(String),
+    // the input ran out before a value the program reads
+    MissingInput,
+    Bfasm(BfasmError),
+    // the values the program would have had errors with when given the input
+    Ops(Vec<OpError>),
+    Interp(BFError),
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::Tokenize => write!(f, "Failed to tokenize the source"),
+            CompileError::Parse(Some((line, column))) => {
+                write!(f, "Failed to parse the statement at {}:{}", line, column)
+            }
+            CompileError::Parse(None) => write!(f, "Failed to parse the source"),
+            CompileError::Type(msg) => write!(f, "Type error: {}", msg),
+            CompileError::MissingInput => write!(f, "The program reads more input than it was given"),
+            CompileError::Bfasm(err) => write!(f, "{}", err),
+            CompileError::Ops(errs) => {
+                write!(f, "The program failed with the given input: ")?;
+
+                errs.iter()
+                    .map(|err| BfasmError::OpError(err.clone()).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+                    .fmt(f)
+            }
+            CompileError::Interp(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+// compiles bunf source to brainfuck
+// the input is the input the program is checked with while it is compiled
+#[derive(Debug, Clone)]
+pub struct Compiler {
+    source: String,
+    input: String,
+    eof: EofPolicy,
+    cells: CellModel,
+    optimize: bool,
+    trace: Option<Tracer>,
+    step_limit: usize,
+}
+
+impl Compiler {
+    pub fn new(source: &str) -> Self {
+        Compiler {
+            source: String::from(source),
+            input: String::new(),
+            eof: EofPolicy::default(),
+            cells: CellModel::U32,
+            optimize: true,
+            trace: None,
+            step_limit: bfasm::STEP_LIMIT,
+        }
+    }
+
+    pub fn input(self, input: &str) -> Self {
+        Compiler { input: String::from(input), ..self }
+    }
+
+    pub fn eof(self, eof: EofPolicy) -> Self {
+        Compiler { eof, ..self }
+    }
+
+    pub fn cells(self, cells: CellModel) -> Self {
+        Compiler { cells, ..self }
+    }
+
+    // runs the passes over the statements and the written code
+    pub fn optimize(self, optimize: bool) -> Self {
+        Compiler { optimize, ..self }
+    }
+
+    pub fn trace(self, trace: Tracer) -> Self {
+        Compiler { trace: Some(trace), ..self }
+    }
+
+    // the most steps the loops and the checked code are run for
+    pub fn step_limit(self, step_limit: usize) -> Self {
+        Compiler { step_limit, ..self }
+    }
+
+    pub fn compile(&self) -> Result<CompileOutput, CompileError> {
+        let bfasm = self.compile_bfasm()?;
+
+        Ok(CompileOutput {
+            code: bfasm.output.as_bfops().clone(),
+            expected_output: bfasm.expected_output.clone(),
+            source_map: bfasm.source_map(),
+        })
+    }
+
+    // finds the cost of each top level statement for the compiler's input
+    // the costs are from before the peephole pass
+    pub fn cost_report(&self) -> Result<Vec<StatementCost>, CompileError> {
+        let anno = self.annotate()?;
+
+        let (statement_ops, clear_ops) = statements_to_bfasm(&mut Vec::new(), &anno, &mut self.input.chars())?;
+
+        let mut bfasm = self.new_bfasm();
+
+        let code: Vec<BfasmOps> = statement_ops.iter().flat_map(|(_, code)| code).cloned().collect();
+
+        let exec_error = |err| match err {
+            BfasmError::OpError(err) => CompileError::Ops(vec![err]),
+            err => CompileError::Bfasm(err),
+        };
+
+        let ranges = BfasmOps::exec_ranges(&code, &mut bfasm).map_err(exec_error)?;
+
+        BfasmOps::exec_ranges(&clear_ops, &mut bfasm).map_err(exec_error)?;
+
+        // the whole program is only run once
+        let mut costs = bfasm.costs(&code, &ranges).map_err(CompileError::Interp)?.into_iter();
+
+        Ok(anno
+            .0
+            .iter()
+            .zip(statement_ops)
+            .map(|((_, statement), (_, code))| {
+                let ops: Vec<OpCost> = costs.by_ref().take(code.len()).collect();
+
+                StatementCost {
+                    statement: format!("{:?}", statement),
+                    instructions: ops.iter().map(|op| op.instructions).sum(),
+                    steps: ops.iter().map(|op| op.steps).sum(),
+                    ops,
+                }
+            })
+            .collect())
+    }
+
+    // the statements of the source after the passes have been run over them
+    fn annotate(&self) -> Result<AnnotatedBlock, CompileError> {
+        let (tokens, spans) = tokenize_spans(&self.source).ok_or(CompileError::Tokenize)?;
+
+        let statements = tokens_to_spanned_statements(&tokens, &spans)
+            .map_err(|index| CompileError::Parse(index.and_then(|index| spans.get(index).copied())))?;
+
+        let mut anno = annotate_statements(&statements, &mut Vec::new())?;
+
+        if self.optimize {
+            fold_constants(&mut anno);
+            remove_dead_assigns(&mut anno);
+            layout_block(&mut anno);
+        }
+
+        Ok(anno)
+    }
+
+    fn new_bfasm(&self) -> Bfasm {
+        Bfasm {
+            eof: self.eof,
+            cells: self.cells,
+            trace: self.trace.clone(),
+            step_limit: Some(self.step_limit),
+            ..Bfasm::default()
+        }
+    }
+
+    fn compile_bfasm(&self) -> Result<Bfasm, CompileError> {
+        let anno = self.annotate()?;
+
+        let code = annostatements_to_bfasm(&mut Vec::new(), &anno, &mut self.input.chars())?;
+
+        let mut bfasm = self.new_bfasm();
+
+        match BfasmOps::full_exec(&code, &mut bfasm).map_err(CompileError::Bfasm)? {
+            None => {
+                if self.optimize {
+                    bfasm.peephole().map_err(CompileError::Interp)?;
+                }
+
+                Ok(bfasm)
+            }
+            Some(errs) => Err(CompileError::Ops(errs)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompileOutput {
+    pub code: Vec<BFOp>,
+    // what the code outputs when given the compiler's input
+    pub expected_output: String,
+    pub source_map: String,
+}
+
+impl Display for CompileOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&BFOp::as_code(&self.code))
+    }
+}
+
+// runs brainfuck code
+#[derive(Debug, Clone)]
+pub struct Interpreter {
+    interp: BFInterpreter,
+}
+
+impl Interpreter {
+    // any char that isn't one of the eight instructions is ignored
+    pub fn new(code: &str) -> Self {
+        Interpreter::from_ops(BFOp::from_str(code))
+    }
+
+    pub fn from_ops(code: Vec<BFOp>) -> Self {
+        Interpreter {
+            interp: BFInterpreter::new(code, String::new()),
+        }
+    }
+
+    pub fn input(mut self, input: &str) -> Self {
+        self.interp.input = String::from(input);
+        self
+    }
+
+    pub fn eof(mut self, eof: EofPolicy) -> Self {
+        self.interp.eof = eof;
+        self
+    }
+
+    pub fn cells(mut self, cells: CellModel) -> Self {
+        self.interp.cells = cells;
+        self
+    }
+
+    pub fn trace(mut self, trace: Tracer) -> Self {
+        self.interp.trace = Some(trace);
+        self
+    }
+
+    // reads from the reader instead of the input
+    pub fn reader(mut self, reader: Reader) -> Self {
+        self.interp.reader = Some(reader);
+        self
+    }
+
+    // writes to the writer instead of keeping the output so output and run return nothing
+    pub fn writer(mut self, writer: Writer) -> Self {
+        self.interp.writer = Some(writer);
+        self
+    }
+
+    pub fn step_limit(mut self, limit: usize) -> Self {
+        self.interp.step_limit = Some(limit);
+        self
+    }
+
+    // the most cells the code can use
+    pub fn tape_limit(mut self, limit: usize) -> Self {
+        self.interp.tape_limit = Some(limit);
+        self
+    }
+
+    // keeps every step so it can be undone
+    pub fn record(mut self) -> Self {
+        self.interp.history = Some(Vec::new());
+        self
+    }
+
+    pub fn step_back(&mut self) -> bool {
+        self.interp.step_back()
+    }
 
-    let statements = tokens_to_statements(&tokens).unwrap();
+    pub fn run_back_to(&mut self, label: &str) -> bool {
+        self.interp.run_back_to(label)
+    }
+
+    // returns the output, which is empty if a writer was set
+    pub fn run(&mut self) -> Result<&str, BFError> {
+        self.interp.run()?;
+
+        Ok(&self.interp.output)
+    }
+
+    pub fn run_for(&mut self, steps: usize) -> Result<RunStatus, BFError> {
+        self.interp.run_for(steps)
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        self.interp.snapshot()
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), BFError> {
+        self.interp.restore(snapshot)
+    }
 
-    let anno = annotate_statements(&statements, &mut Vec::new());
+    pub fn output(&self) -> &str {
+        &self.interp.output
+    }
 
-    let code = dbg!(annostatements_to_bfasm(&mut Vec::new(), &anno, input));
+    pub fn tape(&self) -> &[u32] {
+        &self.interp.array
+    }
 
-    let mut bfasm = Bfasm::default();
+    pub fn pointer(&self) -> usize {
+        self.interp.array_index
+    }
 
-    match BfasmOps::full_exec(&code, &mut bfasm).unwrap(){
-        None => {Ok(bfasm)}
-        Some(errs) => {Err(errs)}
+    pub fn steps(&self) -> usize {
+        self.interp.steps
     }
 }
+
+// the code a top level statement wrote and how much of it was run
+#[derive(Debug, Clone)]
+pub struct StatementCost {
+    pub statement: String,
+    pub instructions: usize,
+    pub steps: usize,
+    pub ops: Vec<OpCost>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bfasm::BfasmWriter;
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+    use crate::program::bfstd;
     use crate::program::main;
     use std::fs;
 
@@ -1385,13 +2847,13 @@ mod tests {
 
         let mut vec = Vec::new();
 
-        let anno = annotate_statements(&statements, &mut vec);
+        let anno = annotate_statements(&statements, &mut vec).unwrap();
 
         let mut vec2 = Vec::new();
 
         let mut input = ",+.\0a".chars(); // ,+.�a
 
-        let mut code = dbg!(annostatements_to_bfasm(&mut vec2, &anno, &mut input));
+        let mut code = dbg!(annostatements_to_bfasm(&mut vec2, &anno, &mut input).unwrap());
         code.pop();
         code.pop();
         code.pop();
@@ -1411,6 +2873,69 @@ mod tests {
 
     }
 
+    // the bunf source inside the main of program.rs
+    fn program_body(file: &str) -> &str {
+        &file[file.find("fn main() {").unwrap() + "fn main() {".len()..file.rfind('}').unwrap()]
+    }
+
+    #[test]
+    fn self_host_test() {
+        let file = fs::read_to_string("./src/program.rs").unwrap();
+
+        let output = Compiler::new(program_body(&file)).input("++[>+++<-]>.\0a").compile().unwrap();
+
+        assert_eq!(output.expected_output, "\u{6}");
+
+        // the compiled interpreter runs other programs
+        for (program, input, expected) in [(",[.,]", "hi", "hi"), ("+[[-]+>]+++<[>+<-]>.", "", "\u{4}")] {
+            let mut interp = Interpreter::from_ops(output.code.clone())
+                .input(&format!("{program}\0{input}"))
+                .step_limit(100_000_000);
+
+            assert_eq!(interp.run().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn native_test() {
+        let file = fs::read_to_string("./src/program.rs").unwrap();
+
+        let input = ",[>+<-]>.\0a";
+
+        let output = Compiler::new(program_body(&file)).input(input).compile().unwrap();
+
+        // the program run as rust is the reference for the compiled code
+        assert_eq!(output.expected_output, bfstd::run(main, input));
+        assert_eq!(bfstd::run(main, input), "a");
+
+        for input in [
+            ",[.,]\0hello",
+            "++[>++<-]>[<+>-]<.\0",
+            "++[>++[>+<-]<-]>>.\0",
+            ">>+<<[-]>>[<<+>>-]<<.>[]+.\0",
+            ">,[>,]<[.<]\0abc",
+        ] {
+            let mut interp = Interpreter::from_ops(output.code.clone())
+                .input(input)
+                .step_limit(100_000_000);
+
+            assert_eq!(interp.run().unwrap(), bfstd::run(main, input), "{:?}", input);
+        }
+
+        // the native run also has to match the golden snapshot of the compiled interpreter
+        let input = fs::read_to_string("./tests/programs/interpreter.input").unwrap();
+        let expected = fs::read_to_string("./tests/programs/interpreter.expected_output").unwrap();
+
+        assert_eq!(bfstd::run(main, &input), expected);
+    }
+
+    #[test]
+    fn comment_test() {
+        let code = "let x = 1; // x / 2\n// let y = 2;\nprint_u32(x);";
+
+        assert_eq!(tokenize(code), tokenize("let x = 1; print_u32(x);"));
+    }
+
     #[test]
     fn test3() {
         let code = "";
@@ -1455,9 +2980,610 @@ mod tests {
         assert!(bfasm.test_run().unwrap())
     }
 
+    #[test]
+    fn string_test() {
+        let code = "
+            let mut output = String::new();
+            output.push('b');
+            output.push('c');
+            output.insert(0, 'a');
+            output += \"de\";
+            let len = output.len();
+            print_u32(len);
+            print_u32(output.chars().nth(0).unwrap());
+            print_u32(output.chars().nth(4).unwrap());";
+
+        let mut bfasm = bunf(code, &mut "".chars()).unwrap();
+
+        assert_eq!(bfasm.expected_output, "\u{5}ae");
+
+        assert!(bfasm.test_run().unwrap())
+    }
+
+    #[test]
+    fn array_index_test() {
+        let code = "
+            let mut a = new_array();
+            a.push(3);
+            a.push(4);
+            a.push(5);
+            let first = 0;
+            let last = 2;
+            print_u32(a[first]);
+            print_u32(a[last]);
+            a[first] = 6;
+            a[last] = 7;
+            print_u32(a[0]);
+            print_u32(a[1]);
+            print_u32(a[2]);";
+
+        for optimize in [true, false] {
+            let output = Compiler::new(code).optimize(optimize).compile().unwrap();
+
+            assert_eq!(output.expected_output, "\u{3}\u{5}\u{6}\u{4}\u{7}");
+            assert_eq!(Interpreter::from_ops(output.code).run().unwrap(), "\u{3}\u{5}\u{6}\u{4}\u{7}");
+        }
+
+        // an index past either end is an error instead of wrapping around
+        for (code, index) in [
+            ("let mut a = new_array(); a.push(3); let i = 1; print_u32(a[i]);", 1),
+            ("let mut a = new_array(); a.push(3); let i = 1; a[i] = 2;", 1),
+            ("let mut a = new_array(); let i = 0; print_u32(a[i]);", 0),
+            ("let mut a = new_array(); let i = 0; a[i] = 2;", 0),
+        ] {
+            for optimize in [true, false] {
+                let err = Compiler::new(code).optimize(optimize).compile().unwrap_err();
+
+                assert!(
+                    matches!(&err, CompileError::Ops(errs) if matches!(errs[..], [OpError::InvalidArrayIndex(found)] if found == index)),
+                    "{code} {err:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn push_str_test() {
+        let code = "
+            let input = input_str();
+            let mut output = String::new();
+            output.push_str(&input);
+            output.push_str(\"!\");
+            print_u32(output.chars().nth(5).unwrap());";
+
+        let mut bfasm = bunf(code, &mut "hello\0".chars()).unwrap();
+
+        assert_eq!(bfasm.expected_output, "!");
+
+        assert!(bfasm.test_run().unwrap())
+    }
+
+    #[test]
+    fn add_assign_str_test() {
+        let code = "
+            let input = input_str();
+            let mut output = String::new();
+            output += &input;
+            output += input;
+            output += \"!\";
+            let mut len = output.len();
+            let one = 1;
+            len += one;
+            print_u32(len);
+            print_u32(output.chars().nth(2).unwrap());
+            print_u32(output.chars().nth(4).unwrap());";
+
+        let mut bfasm = bunf(code, &mut "ab\0".chars()).unwrap();
+
+        assert_eq!(bfasm.expected_output, "\u{6}a!");
+
+        assert!(bfasm.test_run().unwrap())
+    }
+
+    #[test]
+    fn str_compare_test() {
+        let code = "
+            let input = input_str();
+            let mut prefix = String::new();
+            prefix.push_str(\"go\");
+            let mut count = 0;
+            if input.starts_with(&prefix) {
+                count += 1;
+            }
+            if input == \"go north\" {
+                count += 1;
+            }
+            if input == prefix {
+                count += 1;
+            }
+            if input.contains('n') {
+                count += 1;
+            }
+            let index = input.find('n').unwrap();
+            print_u32(count);
+            print_u32(index);";
+
+        let mut bfasm = bunf(code, &mut "go north\0".chars()).unwrap();
+
+        assert_eq!(bfasm.expected_output, "\u{3}\u{3}");
+
+        assert!(bfasm.test_run().unwrap())
+    }
+
+    #[test]
+    fn read_line_test() {
+        let code = "
+            let first = read_line();
+            let second = read_line();
+            if first == second {
+                print_u32(1);
+            }
+            let len = second.len();
+            print_u32(len);";
+
+        // the last line can be ended by eof
+        for input in ["abc\nab\n", "abc\nab"] {
+            let mut bfasm = bunf(code, &mut input.chars()).unwrap();
+
+            assert_eq!(bfasm.expected_output, "\u{2}");
+            assert_eq!(bfasm.expected_input, input);
+
+            assert!(bfasm.test_run().unwrap())
+        }
+    }
+
+    #[test]
+    fn read_to_end_test() {
+        let code = "
+            let text = read_to_end();
+            let len = text.len();
+            print_u32(len);";
+
+        let mut bfasm = bunf(code, &mut "ab\ncd".chars()).unwrap();
+
+        assert_eq!(bfasm.expected_output, "\u{5}");
+
+        assert!(bfasm.test_run().unwrap())
+    }
+
+    // returns the length of the code and the number of steps it took to run
+    fn layout_stats(code: &str, input: &str, layout: bool) -> (usize, usize) {
+        let tokens = tokenize(code).unwrap();
+
+        let statements = tokens_to_statements(&tokens).unwrap();
+
+        let mut anno = annotate_statements(&statements, &mut Vec::new()).unwrap();
+
+        if layout {
+            layout_block(&mut anno);
+        }
+
+        let code = annostatements_to_bfasm(&mut Vec::new(), &anno, &mut input.chars()).unwrap();
+
+        let mut bfasm = Bfasm::default();
+
+        assert!(BfasmOps::full_exec(&code, &mut bfasm).unwrap().is_none());
+
+        assert!(bfasm.test_run().unwrap());
+
+        let BfasmWriter::BFInterp(interp, _) = &bfasm.output else {
+            unreachable!()
+        };
+
+        (interp.instructions.len(), interp.steps)
+    }
+
+    #[test]
+    fn layout_test() {
+        let file = fs::read_to_string("./src/program.txt").unwrap();
+
+        let file = &file[file.find('{').unwrap() + 1..file.rfind('}').unwrap()];
+
+        let input = "++++++++,+.\0a";
+
+        let (len, steps) = layout_stats(file, input, false);
+        let (layout_len, layout_steps) = layout_stats(file, input, true);
+
+        assert!(layout_len < len);
+        assert!(layout_steps < steps);
+
+        // the string is made after x so pushing to it doesn't move x
+        let code = "
+            let mut output = String::new();
+            let mut x = 60;
+            x += 5;
+            output += \"abcdefgh\";
+            print_u32(x);";
+
+        let (len, steps) = layout_stats(code, "", false);
+        let (layout_len, layout_steps) = layout_stats(code, "", true);
+
+        assert!(layout_len < len);
+        assert!(layout_steps < steps);
+    }
+
+    #[test]
+    fn dead_var_test() {
+        let tokens = tokenize("let unused = 5; let x = input_u32(); let y = 1; y += 1;").unwrap();
+
+        let mut anno = annotate_statements(&tokens_to_statements(&tokens).unwrap(), &mut Vec::new()).unwrap();
+
+        remove_dead_assigns(&mut anno);
+
+        // input is still read even though x is never used
+        assert_eq!(anno.0.len(), 3);
+        assert_eq!(anno.1.len(), 2);
+
+        let tape_len = |code: &str| {
+            let mut bfasm = bunf(code, &mut "".chars()).unwrap();
+
+            assert!(bfasm.expected_output.starts_with("\u{3}\u{4}"));
+            assert!(bfasm.test_run().unwrap());
+
+            let BfasmWriter::BFInterp(interp, _) = &bfasm.output else {
+                unreachable!()
+            };
+
+            interp.array.len()
+        };
+
+        // the unused values still fail like they do when they aren't removed
+        for source in [
+            "let s = String::new(); s.push('a'); let i = s.find('z').unwrap();",
+            "let y = 1; y -= 2;",
+        ] {
+            for optimize in [true, false] {
+                let err = Compiler::new(source).optimize(optimize).compile().unwrap_err();
+
+                assert!(matches!(err, CompileError::Ops(_)), "{} {:?}", source, err);
+            }
+        }
+
+        let err = Compiler::new("let s = String::new(); s.push('a'); let i = s.find('z').unwrap();").compile().unwrap_err();
+
+        assert!(matches!(err, CompileError::Ops(errs) if matches!(errs[..], [OpError::CharNotFound(b'z')])));
+
+        // b can use the cells a had
+        let reused = tape_len("let a = 3; print_u32(a); let b = 4; print_u32(b);");
+        let separate = tape_len("let a = 3; let b = 4; print_u32(a); print_u32(b); print_u32(a);");
+
+        assert!(reused < separate);
+    }
+
+    #[test]
+    fn fold_test() {
+        let code = "
+            let x = 5;
+            if 4 < 5 {
+                print_u32(x);
+            }
+            if 2 == 2 {
+                let y = 6;
+                print_u32(y);
+            }
+            while 3 < 2 {
+                print_u32(1);
+            }
+            if 'a' == 'b' {
+                print_u32(1);
+            }
+            match 'b' {
+                'a' => {
+                    print_u32(1);
+                },
+                'b' => {
+                    print_u32(2);
+                },
+                _ => {}
+            }";
+
+        let tokens = tokenize(code).unwrap();
+
+        let mut anno = annotate_statements(&tokens_to_statements(&tokens).unwrap(), &mut Vec::new()).unwrap();
+
+        fold_constants(&mut anno);
+
+        assert!(anno.0.iter().all(|(_, statement)| matches!(statement, AnnotatedStatement::Function(_))));
+        assert_eq!(anno.0.len(), 5);
+
+        let mut bfasm = bunf(code, &mut "".chars()).unwrap();
+
+        assert_eq!(bfasm.expected_output, "\u{5}\u{6}\u{2}");
+
+        assert!(bfasm.test_run().unwrap());
+
+        // an inlined block keeps its variables apart from the ones declared after it
+        let code = "
+            if true {
+                let y = 5;
+                print_u32(y);
+            }
+            match 'a' {
+                'a' => {
+                    let y = 6;
+                    print_u32(y);
+                },
+                _ => {}
+            }
+            let y = input_str();
+            print_u32(y.len());";
+
+        let output = Compiler::new(code).input("ab\0").compile().unwrap();
+
+        assert_eq!(output.expected_output, "\u{5}\u{6}\u{2}");
+
+        let mut value = Value::Func(Box::new(Function::GreaterThan(
+            Value::Func(Box::new(Function::Add(
+                Value::Static(Type::U32(2)),
+                Value::Static(Type::U32(3)),
+            ))),
+            Value::Static(Type::U32(4)),
+        )));
+
+        fold_value(&mut value);
+
+        assert_eq!(value, Value::Static(Type::Bool(true)));
+
+        // underflows are left for the code to find
+        let mut value = Value::Func(Box::new(Function::Subtract(
+            Value::Static(Type::U32(2)),
+            Value::Static(Type::U32(3)),
+        )));
+
+        fold_value(&mut value);
+
+        assert!(matches!(value, Value::Func(_)));
+    }
+
+    #[test]
+    fn source_map_test() {
+        let code = "let x = 3;\nlet y = 4;\n  print_u32(y);\nprint_u32(x);";
+
+        let (tokens, spans) = tokenize_spans(code).unwrap();
+
+        assert_eq!(tokens.len(), spans.len());
+        assert_eq!(spans[0], (1, 1));
+        assert_eq!(spans[10], (3, 3));
+
+        let bfasm = bunf(code, &mut "".chars()).unwrap();
+
+        let map = bfasm.source_map();
+
+        assert!(map.starts_with("{\"mappings\":[{\"start\":0,"));
+        assert!(map.find("\"line\":3,\"column\":3}").unwrap() < map.find("\"line\":4,\"column\":1}").unwrap());
+
+        // a removed statement takes its position with it
+        let code = "let x = 3;\nlet z = 7;\nprint_u32(x);";
+
+        let map = bunf(code, &mut "".chars()).unwrap().source_map();
+
+        assert!(!map.contains("\"line\":2,"));
+        assert!(map.contains("\"line\":3,\"column\":1}"));
+    }
+
+    #[test]
+    fn compiler_test() {
+        let output = Compiler::new("let x = 3; print_u32(x); print_u32(input_u32());")
+            .input("a")
+            .cells(CellModel::Wrapping8)
+            .compile()
+            .unwrap();
+
+        assert_eq!(output.expected_output, "\u{3}a");
+        assert!(output.to_string().chars().all(|char| "+-<>,.[]".contains(char)));
+
+        let mut interp = Interpreter::new(&output.to_string())
+            .input("a")
+            .cells(CellModel::Wrapping8);
+
+        assert_eq!(interp.run().unwrap(), "\u{3}a");
+
+        assert!(matches!(Compiler::new("print_u32('a").compile(), Err(CompileError::Tokenize)));
+
+        // the subtraction's code fails when it's checked so only the op error is returned
+        let err = Compiler::new("let x = 0; x -= 1; print_u32(x);").optimize(false).compile().unwrap_err();
+
+        assert!(matches!(err, CompileError::Ops(errs) if matches!(errs[..], [OpError::Underflow])));
+
+        let err = Interpreter::new("+<").run().unwrap_err();
+
+        assert_eq!(err.kind, BFErrorKind::NegativeArrayPointer);
+    }
+
+    #[test]
+    fn compile_error_test() {
+        let compile = |source: &str| Compiler::new(source).compile().unwrap_err();
+
+        assert!(matches!(compile("let x = 5 $ 3;"), CompileError::Tokenize));
+
+        // only comparisons have operators
+        assert!(matches!(compile("let x = 5 - 3;"), CompileError::Parse(Some((1, 1)))));
+        assert!(matches!(compile("let x = 2;\n  5 + 3;"), CompileError::Parse(Some((2, 3)))));
+
+        assert!(matches!(compile("foo();"), CompileError::Parse(Some((1, 1)))));
+        assert!(matches!(compile("let s = String::new(); s.bar(1);"), CompileError::Parse(Some((1, 24)))));
+        assert!(matches!(compile("print_u32(1, 2);"), CompileError::Parse(Some((1, 1)))));
+
+        assert_eq!(compile("let x = 2;\n  5 + 3;").to_string(), "Failed to parse the statement at 2:3");
+
+        assert!(matches!(compile("print_u32(y);"), CompileError::Type(_)));
+        assert!(matches!(compile("let x = 1; if x { print_u32(x); }"), CompileError::Type(_)));
+        // y is only declared inside the if
+        assert!(matches!(compile("let x = 1; if x > 0 { let y = 2; } print_u32(y);"), CompileError::Type(_)));
+
+        // the receivers and values of functions are checked before the code is written
+        for source in [
+            "let x = 1; x.push('a');",
+            "let s = String::new(); s.insert(0, 5);",
+            "let a = new_array(); a.insert(0, 'c');",
+            "print_u32(String::new());",
+            "let x = 1; let x = 'a'; print_u32(x);",
+            "let s = String::new(); if s.contains(5) { print_u32(1); }",
+            "let s = String::new(); let t = 3; if s == t { print_u32(1); }",
+            "let x = 3; x += \"ab\";",
+            "let a = new_array(); a.push('c');",
+            "let a = new_array(); print_u32(a[0 == 0]);",
+        ] {
+            assert!(matches!(compile(source), CompileError::Type(_)), "{}", source);
+        }
+
+        assert!(matches!(compile("print_u32(input_u32());"), CompileError::MissingInput));
+        assert!(matches!(compile("let s = input_str(); print_u32(s.len());"), CompileError::MissingInput));
+
+        // chars are compared as numbers even when the comparison isn't folded
+        let output = Compiler::new("if 'a' < 'b' { print_u32(1); }").optimize(false).compile().unwrap();
+
+        assert_eq!(output.expected_output, "\u{1}");
+    }
+
+    #[test]
+    fn stream_test() {
+        // the bytes written and the number of flushes
+        #[derive(Default)]
+        struct Output(Vec<u8>, usize);
+
+        impl std::io::Write for Output {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                self.1 += 1;
+                Ok(())
+            }
+        }
+
+        let output = Arc::new(Mutex::new(Output::default()));
+
+        let mut interp = Interpreter::new(",[.,]")
+            .reader(Reader::new(Cursor::new("hello")))
+            .writer(Writer::shared(output.clone()));
+
+        assert_eq!(interp.run().unwrap(), "");
+        assert_eq!(output.lock().unwrap().0, b"hello");
+        assert_eq!(output.lock().unwrap().1, 1);
+
+        // a read is the same cost however far into the input it is
+        let input = "a".repeat(200_000);
+
+        let mut interp = Interpreter::new(",[.,]").input(&input);
+
+        assert_eq!(interp.run().unwrap(), input);
+    }
+
+    #[test]
+    fn thread_test() {
+        let compiler = Compiler::new("print_u32(3);").trace(Tracer::new(|_| {}));
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+
+        let mut interp = Interpreter::new(",.")
+            .reader(Reader::new(Cursor::new("a")))
+            .writer(Writer::shared(output.clone()));
+
+        // the hooks can be sent to another thread with the compiler and interpreter
+        std::thread::spawn(move || {
+            compiler.compile().unwrap();
+            interp.run().unwrap();
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(*output.lock().unwrap(), b"a");
+    }
+
+    #[test]
+    fn snapshot_test() {
+        let code = ",[.+>,]";
+
+        let mut interp = Interpreter::new(code).input("abc");
+
+        assert_eq!(interp.run_for(10).unwrap(), RunStatus::Suspended);
+
+        let snapshot = interp.snapshot();
+
+        assert_eq!(Snapshot::parse(&snapshot.to_string()), Some(snapshot.clone()));
+
+        assert_eq!(interp.run_for(1000).unwrap(), RunStatus::Finished);
+        assert_eq!(interp.output(), "abc");
+
+        // a new interpreter carries on from the snapshot
+        let mut resumed = Interpreter::new(code).input("abc");
+        resumed.restore(&Snapshot::parse(&snapshot.to_string()).unwrap()).unwrap();
+
+        assert_eq!(resumed.run().unwrap(), "abc");
+        assert_eq!(resumed.steps(), interp.steps());
+        assert_eq!(resumed.tape(), interp.tape());
+
+        // a snapshot that doesn't fit is turned down and the state is kept
+        let broken = [
+            Snapshot { array_index: snapshot.array.len(), ..snapshot.clone() },
+            Snapshot { instruction_index: code.len() + 1, ..snapshot.clone() },
+            Snapshot { input_index: 4, ..snapshot.clone() },
+        ];
+
+        for snapshot in &broken {
+            assert_eq!(resumed.restore(snapshot).unwrap_err().kind, BFErrorKind::InvalidSnapshot);
+        }
+
+        assert_eq!(resumed.tape(), interp.tape());
+    }
+
+    #[test]
+    fn limit_test() {
+        let kind = |interp: Interpreter| interp.clone().run().map(|_| ()).unwrap_err().kind;
+
+        assert_eq!(kind(Interpreter::new("+[>+]").step_limit(1000)), BFErrorKind::StepLimit);
+        assert_eq!(kind(Interpreter::new("+[>+]").tape_limit(100)), BFErrorKind::TapeLimit);
+        assert_eq!(kind(Interpreter::new("+[]").step_limit(1000)), BFErrorKind::InfiniteLoop);
+        assert_eq!(kind(Interpreter::new("+[[]]").step_limit(1000)), BFErrorKind::InfiniteLoop);
+
+        // the inner loop changes the tape
+        assert!(Interpreter::new("+++[>++[-]<-]").step_limit(1000).run().is_ok());
+        assert!(Interpreter::new("[]+[-]").run().is_ok());
+
+        // the loops never end so they run out of steps instead of compiling forever
+        for source in ["while true { print_u32(1); }", "let x = 1; if x > 0 { while x > 0 { x += 1; } }"] {
+            let err = Compiler::new(source).step_limit(1000).compile().unwrap_err();
+
+            assert!(matches!(err, CompileError::Bfasm(BfasmError::StepLimit)), "{}", source);
+        }
+
+        let output = Compiler::new("let x = 1; if x > 0 { while x < 5 { x += 1; } } print_u32(x);")
+            .step_limit(10_000)
+            .compile()
+            .unwrap();
+
+        assert_eq!(output.expected_output, "\u{5}");
+    }
+
+    #[test]
+    fn cost_report_test() {
+        let code = "
+            let x = 3;
+            let i = 4;
+            while i > 0 {
+                print_u32(x);
+                i -= 1;
+            }";
+
+        let report = Compiler::new(code).cost_report().unwrap();
+
+        assert_eq!(report.len(), 3);
+
+        report.iter().for_each(|statement| {
+            assert_eq!(statement.instructions, statement.ops.iter().map(|op| op.instructions).sum());
+        });
+
+        // the loop is run four times
+        assert!(report[2].steps > report[2].instructions * 2);
+        assert!(report[0].steps <= report[0].instructions);
+    }
+
     #[test]
     fn norm_program() {
-        main()
+        assert_eq!(bfstd::run(main, "+++[->+<]>.\0"), "\u{3}");
     }
 
     #[test]
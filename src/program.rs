@@ -1,6 +1,10 @@
+// match guards aren't part of the BunF subset
+#![allow(clippy::collapsible_match)]
+
 use crate::program::bfstd::*;
-mod bfstd;
+pub(crate) mod bfstd;
 
+// the body is also the bunf source of the interpreter in tests/programs
 pub(crate) fn main() {
     let program = input_str();
 
@@ -54,10 +58,13 @@ pub(crate) fn main() {
 
                         program_index += 1;
                     }
+
+                    // the end of the main loop moves past the bracket
+                    program_index -= 1;
                 }
             }
             ']' => {
-                if array[array_index] > 1 {
+                if array[array_index] > 0 {
                     let mut bracket_count = 1;
 
                     program_index -= 1;
@@ -1,29 +1,51 @@
-pub fn input_u32() -> u32 {
-    // let mut line = String::new();
-    //
-    // std::io::stdin().read_line(&mut line).unwrap();
-    //
-    // return line.chars().next().unwrap() as u32;
+use std::cell::RefCell;
+use std::mem;
+
+// the input the program reads from and what it has printed
+#[derive(Default)]
+struct Io {
+    input: Vec<char>,
+    index: usize,
+    output: String,
+}
+
+thread_local! {
+    static IO: RefCell<Io> = RefCell::new(Io::default());
+}
+
+// runs the program natively with the input and returns what it printed
+pub fn run(program: fn(), input: &str) -> String {
+    IO.with(|io| {
+        *io.borrow_mut() = Io {
+            input: input.chars().collect(),
+            ..Io::default()
+        }
+    });
+
+    program();
 
-    'a' as u32
+    IO.with(|io| mem::take(&mut io.borrow_mut().output))
+}
+
+fn read() -> Option<char> {
+    IO.with(|io| {
+        let mut io = io.borrow_mut();
+
+        let char = io.input.get(io.index).copied();
+        io.index += 1;
+
+        char
+    })
+}
+
+pub fn input_u32() -> u32 {
+    // the compiled code reads a 0 once the input runs out
+    read().map_or(0, |char| char as u32)
 }
 
 pub fn input_str() -> String {
-    // let mut line = String::new();
-    //
-    // use std::io::{stdin, stdout, Write};
-    //
-    // print!("Please enter some text: ");
-    // let _ = stdout().flush();
-    // stdin()
-    //     .read_line(&mut line)
-    //     .expect("Did not enter a correct string");
-    //
-    // println!("You typed: {}", &line);
-    //
-    // line
-
-    String::from("+++[->+<]")
+    // a nul char ends the string the same way the end of the input does
+    std::iter::from_fn(read).take_while(|char| *char != '\0').collect()
 }
 
 pub fn new_array() -> Vec<u32> {
@@ -31,7 +53,7 @@ pub fn new_array() -> Vec<u32> {
 }
 
 pub fn print_u32(str: u32) {
-    print!("{}", str as u8 as char);
+    IO.with(|io| io.borrow_mut().output.push(str as u8 as char));
 }
 
 // pub fn print_array(string: Vec<u32>) {
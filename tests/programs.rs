@@ -0,0 +1,158 @@
+// compiles each program in tests/programs and checks it against the snapshots next to it
+// name.input is given to the program, name.expected_output and name.bf are what it printed and compiled to
+// the interpreter's source is the main of src/program.rs so it is only written in one place
+// the unit tests run that main natively and check it against the same snapshots
+// running with BUNF_BLESS set rewrites the snapshots from the current compiler
+
+use bunf::{CellModel, Compiler, EofPolicy, Interpreter};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// a miscompiled loop fails here instead of running forever
+const STEP_LIMIT: usize = 100_000_000;
+
+// the programs are compiled and run with the same settings
+const EOF: EofPolicy = EofPolicy::Zero;
+const CELLS: CellModel = CellModel::U32;
+
+// how many instructions are on each line of a .bf snapshot
+const LINE_LENGTH: usize = 80;
+
+// each program's source and the path its snapshots are named after
+fn programs() -> Vec<(PathBuf, String)> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let dir = root.join("tests/programs");
+
+    let mut programs: Vec<(PathBuf, String)> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "bunf"))
+        .map(|path| {
+            let source = fs::read_to_string(&path).unwrap();
+            (path, source)
+        })
+        .collect();
+
+    let file = fs::read_to_string(root.join("src/program.rs")).unwrap();
+
+    programs.push((dir.join("interpreter"), String::from(program_body(&file))));
+
+    programs.sort();
+
+    programs
+}
+
+// the bunf source inside the main of program.rs
+fn program_body(file: &str) -> &str {
+    &file[file.find("fn main() {").unwrap() + "fn main() {".len()..file.rfind('}').unwrap()]
+}
+
+// the code split into lines so a change only shows up on the lines it's on
+fn as_snapshot(code: &str) -> String {
+    code.as_bytes()
+        .chunks(LINE_LENGTH)
+        .map(|line| String::from_utf8(line.to_vec()).unwrap() + "\n")
+        .collect()
+}
+
+// the first line that differs between the snapshot and what was found
+fn diff(name: &str, expected: &str, found: &str) -> Option<String> {
+    if expected == found {
+        return None;
+    }
+
+    let mut expected_lines = expected.lines();
+    let mut found_lines = found.lines();
+    let mut line = 1;
+
+    loop {
+        match (expected_lines.next(), found_lines.next()) {
+            (Some(old), Some(new)) if old == new => line += 1,
+            (old, new) => {
+                return Some(format!(
+                    "{name} differs at line {line}\n  expected {:?}\n  found    {:?}",
+                    old.unwrap_or("the end"),
+                    new.unwrap_or("the end"),
+                ))
+            }
+        }
+    }
+}
+
+// the snapshot is written when blessing or compared against otherwise
+fn snapshot(path: &Path, found: &str, bless: bool) -> Option<String> {
+    let name = path.file_name().unwrap().to_string_lossy();
+
+    if bless {
+        fs::write(path, found).unwrap();
+        return None;
+    }
+
+    match fs::read_to_string(path) {
+        Ok(expected) => diff(&name, &expected, found),
+        Err(_) => Some(format!("{name} is missing, run with BUNF_BLESS=1 to write it")),
+    }
+}
+
+// the ways the program didn't match its snapshots
+fn check(path: &Path, source: &str, bless: bool) -> Vec<String> {
+    let input = fs::read_to_string(path.with_extension("input")).unwrap_or_default();
+
+    let output = match Compiler::new(source).input(&input).eof(EOF).cells(CELLS).compile() {
+        Ok(output) => output,
+        Err(err) => return vec![format!("{} failed to compile with {}", path.display(), err)],
+    };
+
+    let code = output.to_string();
+
+    let mut interp = Interpreter::from_ops(output.code)
+        .input(&input)
+        .eof(EOF)
+        .cells(CELLS)
+        .step_limit(STEP_LIMIT);
+
+    let printed = match interp.run() {
+        Ok(printed) => printed,
+        Err(err) => return vec![format!("{} failed to run with {}", path.display(), err)],
+    };
+
+    let mut failures = Vec::new();
+
+    if printed != output.expected_output {
+        failures.push(format!(
+            "{} printed {:?} but was compiled to print {:?}",
+            path.display(),
+            printed,
+            output.expected_output
+        ));
+    }
+
+    failures.extend(snapshot(&path.with_extension("expected_output"), printed, bless));
+
+    let bf = path.with_extension("bf");
+    let old_size = fs::read_to_string(&bf).map(|old| old.lines().map(str::len).sum::<usize>());
+
+    if let Some(failure) = snapshot(&bf, &as_snapshot(&code), bless) {
+        let size = code.len();
+
+        failures.push(match old_size {
+            Ok(old_size) => format!("{failure}\n  the code went from {old_size} to {size} instructions"),
+            Err(_) => failure,
+        });
+    }
+
+    failures
+}
+
+#[test]
+fn programs_test() {
+    let bless = env::var_os("BUNF_BLESS").is_some();
+    let programs = programs();
+
+    assert!(!programs.is_empty());
+
+    let failures: Vec<String> = programs.iter().flat_map(|(path, source)| check(path, source, bless)).collect();
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
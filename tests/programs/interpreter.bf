@@ -0,0 +1,89 @@
+>>,[[>>]>[->>+<<]>>+<<<<<[[->>+<<]<<]>>,]>>[[-<<+>>]>>]>[-<<+>>]>>>>>>>[-<<+>>]<
+<+[-<<+>>]<[->>+<<]>>+>>>>>>[->+>+<<]>>[-<<+>>]<[->>+<<]<<<<<<<<<<[<<]<<<[->+>+<
+<]>>[-<<+>>]<[->>>>[>>]>>>>>>>>>>>>+<<<<<<<<<<<<<<[<<]<<]>>>>[>>]>>>>>>>>>>>>>+<
+[-<<[->]>]>>[<<+>>>]<-<[-]<<[-]>[-<+>]<[<<<[->+>+<<]>>[-<<+>>]<[->>>+<<<]>>>[-<<
+<<<<<<<<<<<[<<]<<+>>>>[>>]>>>>>>>>>>>]<<<<<<<<<<<<<[<<]<<[-<<<[<]+[>]>>]<<<[<]<[
+->>[>]>>+>+<<<<[<]<]>>[>]>>>[-<<<<[<]<+>>[>]>>>]<<<<[<]>[>->]>>[->>>>[>>]>>>>>>>
+>>>>+<<<<<<<<<<<<<[<<]<<]>>>>[>>]>>>>>>>>>>>>>>>+<<+++++++++++++++++++++++++++++
+++++++++++++++[-<<[->]>]>>[<<<<[>]>>>>[<<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>
+>>>>+<<<<<<<<<<<<]<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<]>>
+>>>>>>>>>>>>>[->-<]>-[-<+>]+<<<<<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>>+<
+<<<<<<<<<<<<<]<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<]>>
+>>>>>>>>>>>>>>>[->-<]>-[-<+>]<[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<
+<<<<<[-<<<[<]+[>]>>]<<<[<]<[->>[>]>>+>+<<<<[<]<]>>[>]>>>[-<<<<[<]<+>>[>]>>>]<<<<
+[<]>[>->]>>-[->>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>[-<+>]<<[-<<<
+<<<<<<<<<<<<+>>>>>>>>>>>>>>>]>[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<
+[-<<<[<]+[>]>>]<<<[<]<[-]+>>[>]>>>[-<<<<[<]<+>>[>]>>>]<<<<[<]>[>->]>>>>>>>>>>>>>
+>>>]<<<+[-<<[->]>]>>[<<<<[>]>>>>[<<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>+<
+<<<<<<<<<<<]<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<]>>>>>>>>
+>>>>>>>[->-<]>-[-<+>],<[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>]>[-<<<<<<<<<<<<<<<+>>>>
+>>>>>>>>>>>]<<<<<<<<<<<<<<<<[-<<<[<]+[>]>>]<<<[<]<[-]+>>[>]>>>[-<<<<[<]<+>>[>]>>
+>]<<<<[<]>[>->]>>>>>>>>>>>>>>>>]<<<+[-<<[->]>]>>[<<<<[>]>>>>[<<<<<<<<<<<[->+>+<<
+]>>[-<<+>>]<[->>>>>>>>>>>>+<<<<<<<<<<<<]<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>>
+>>+<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>[->-<]>-[-<+>]<<<<<<<<<<<<<<[->+>+<<]>>[-<<+>
+>]<[->>>>>>>>>>>>>+<<<<<<<<<<<<<]<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>>>>>+<<<
+<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>[->-<]>-[-<+>]<[-<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>
+>]<<<<<<<<<<<<<<<<[-<<<[<]+[>]>>]<<<[<]<[->>[>]>>+>+<<<<[<]<]>>[>]>>>[-<<<<[<]<+
+>>[>]>>>]<<<<[<]>[>->]>>-[->>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>+[
+-<->]<<[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>]>[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>]<<<<
+<<<<<<<<<<<<[-<<<[<]+[>]>>]<<<[<]<[-]+>>[>]>>>[-<<<<[<]<+>>[>]>>>]<<<<[<]>[>->]>
+>>>>>>>>>>>>>>>]<<<+[-<<[->]>]>>[<<<<[>]>>>>[<<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>
+>>>>>>>>>>+<<<<<<<<<<<<]<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>>>>+<<<<<<<<<<<<<
+<<<]>>>>>>>>>>>>>>>[->-<]>-[-<+>]<[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>]<<<<<<<<<<<<
+<<<[-<<<[<]+[>]>>]<<<[<]<[->>[>]>>+>+<<<<[<]<]>>[>]>>>[-<<<<[<]<+>>[>]>>>]<<<<[<
+]>[>->]>>-[->>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>.[-]<]<<<++++++++++++
+++[-<<[->]>]>>[<<<<[>]>>>>[<<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>+<<<<<<<
+<<<<<]>>>>>>>>>>>>>+[-<->]<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>
+>>>>>]<]<<<++[-<<[->]>]>>[<<<<[>]>>>>[<<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>
+>>>+<<<<<<<<<<<<]>>>>>>>>>>>>>+[-<+>]<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>[-<<<<<<<<<<<
+<<+>>>>>>>>>>>>>]<<<<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>+<<<<<<<<<<<<]<<
+<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>+
+<<[-<<[->]>]>>[<<<+<[>-<[-]]>>>]>-<<[-]<[-<+>]<[[-]<<<<<<<<<<<<<<<+[-<<+>>]<[->>
++<<]>>+>>>>>>>>>>>>[->>+<<]<<<<<[->>+<<]<<<[->>+<<]<<<[->>+<<]>>>>>>>>>>>>>>>]<]
+<<<+++++++++++++++++++++++++++++[-<<[->]>]>>[<<<<[>]>>>>[<<<<<<<<<<<[->+>+<<]>>[
+-<<+>>]<[->>>>>>>>>>>>+<<<<<<<<<<<<]<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>>>>+<
+<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>[->-<]>-[-<+>]<[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>]
+<<<<<<<<<<<<<<<[-<<<[<]+[>]>>]<<<[<]<[->>[>]>>+>+<<<<[<]<]>>[>]>>>[-<<<<[<]<+>>[
+>]>>>]<<<<[<]>[>->]>>-[->>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>+<<[-
+<<[->]>]>>[<<<+<[>-<[-]]>>>]>-<<[-]<[-<+>]<[[-]<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->
+>>>>>>>>+<<<<<<<<<]>>>>>>>>>>+[-<+>]<<<<<<<<<<<[-]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>
+>>>]+[->+>+<<]>>[-<<+>>]<[->>+<<]>>>>>>+<<[-<<[->]>]>>[<<<<[>+<[-]]>>>]>-<<[-]<[
+-<+>]<[<<<<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>+<<<<<<<<<<<<<]>>>>>>>>>>
+>>>[-<<<<<<<<<<<<<<<<<<<<<<<[<<]<<+>>>>[>>]>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<
+<<<<<<<<[<<]<<[-<<<[<]+[>]>>]<<<[<]<[->>[>]>>+>+<<<<[<]<]>>[>]>>>[-<<<<[<]<+>>[>
+]>>>]<<<<[<]>[>->]>>[->>>>[>>]>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<[<<]<
+<]>>>>[>>]>>>>>>>>>>>>>>>>>>>>>>>>>+<<++++++++++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++++[-<<[->]>]>>[<<<<[>]>>>>[<<<<<<
+<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>+<<<<<<<<<]>>>>>>>>>>+[-<+>]<<<<<<<<<<<[-]>>>>>
+>>>>>[-<<<<<<<<<<+>>>>>>>>>>]<]<<<++[-<<[->]>]>>[<<<<[>]>>>>[<<<<<<<<[->+>+<<]>>
+[-<<+>>]<[->>>>>>>>>+<<<<<<<<<]>>>>>>>>>>+[-<->]<<<<<<<<<<<[-]>>>>>>>>>>[-<<<<<<
+<<<<+>>>>>>>>>>]<]<<<]]>[<]>[-]<<[-]<<[-]<<<<<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>
+>>>>>>>>>>+<<<<<<<<<<<<<]>>>>>>>>>>>>>>+[-<+>]<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>[-
+<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[-]<<<[->+>+<<]>>[-<<+>>]<[->>+<<]>>>>>>+<<[-<<[-
+>]>]>>[<<<<[>+<[-]]>>>]>-<<[-]<[-<+>]<]<<<[-]<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>
+>>>>>>+<<<<<<<<<]>>>>>>>>>>+[-<->]<<<<<<<<<<<[-]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>
+>]]<]<<<++[-<<[->]>]>>[<<<<[>]>>>>[<<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>
++<<<<<<<<<<<<]<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<]>>>>>>
+>>>>>>>>>[->-<]>-[-<+>]<[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<[-<<<[<
+]+[>]>>]<<<[<]<[->>[>]>>+>+<<<<[<]<]>>[>]>>>[-<<<<[<]<+>>[>]>>>]<<<<[<]>[>->]>>-
+[->>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>+<<[-<<[->]>]>>[<<<<[>+<[-]
+]>>>]>-<<[-]<[-<+>]<[[-]<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>+<<<<<<<<<]>>>>
+>>>>>>+[-<->]<<<<<<<<<<<[-]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]+[->+>+<<]>>[-<<+>>
+]<[->>+<<]>>>>>>+<<[-<<[->]>]>>[<<<<[>+<[-]]>>>]>-<<[-]<[-<+>]<[<<<<<<<<<<<<<[->
++>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>+<<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<
+<<<<<[<<]<<+>>>>[>>]>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<[<<]<<[-<<<[<]+
+[>]>>]<<<[<]<[->>[>]>>+>+<<<<[<]<]>>[>]>>>[-<<<<[<]<+>>[>]>>>]<<<<[<]>[>->]>>[->
+>>>[>>]>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<[<<]<<]>>>>[>>]>>>>>>>>>>>>>
+>>>>>>>>>>>>+<<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++[-<<[->]>]>>[<<<<[>]>>>>[<<<<<<<<[->+>+<<]>>[-<<+>>]<[
+->>>>>>>>>+<<<<<<<<<]>>>>>>>>>>+[-<->]<<<<<<<<<<<[-]>>>>>>>>>>[-<<<<<<<<<<+>>>>>
+>>>>>]<]<<<++[-<<[->]>]>>[<<<<[>]>>>>[<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>+<<
+<<<<<<<]>>>>>>>>>>+[-<+>]<<<<<<<<<<<[-]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]<]<<<]]
+>[<]>[-]<<[-]<<[-]<<<<<<<<<<<<<<[->+>+<<]>>[-<<+>>]<[->>>>>>>>>>>>>+<<<<<<<<<<<<
+<]>>>>>>>>>>>>>>+[-<->]<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>
+>>>>>>]<[-]<<<[->+>+<<]>>[-<<+>>]<[->>+<<]>>>>>>+<<[-<<[->]>]>>[<<<<[>+<[-]]>>>]
+>-<<[-]<[-<+>]<]<<<[-]]<]<<<]]]]]]]]>[<]>[-]<<[-]<<[-]<<<<[->+>+<<]>>[-<<+>>]<[-
+>>>+<<<]>>>>+[-<+>]<<<<<[-]>>>>[-<<<<+>>>>]<[-]<<<[->+>+<<]>>[-<<+>>]<[->>+<<]<<
+<<<<<<<<[<<]<<<[->+>+<<]>>[-<<+>>]<[->>>>[>>]>>>>>>>>>>>>+<<<<<<<<<<<<<<[<<]<<]>
+>>>[>>]>>>>>>>>>>>>>+<[-<<[->]>]>>[<<+>>>]<-<[-]<<[-]>[-<+>]<]<<<[-]<<<[-]<<<[-]
+<<<[[-]<<]<<<[-]<<<[[-]<<]